@@ -2,10 +2,13 @@
 
 in vec2 vp;
 in vec2 vt;
+uniform vec2 offset;
+uniform vec2 view_scale;
+uniform vec2 view_translation;
 out vec2 st;
 
 
 void main () {
     st = vt;
-    gl_Position = vec4 (vp, 0.0, 1.0);
+    gl_Position = vec4 ((vp + offset) * view_scale + view_translation, 0.0, 1.0);
 }
@@ -0,0 +1,13 @@
+#version 420 core
+
+in vec2 vp;
+in vec4 vc;
+uniform vec2 view_scale;
+uniform vec2 view_translation;
+out vec4 color;
+
+
+void main () {
+    color = vc;
+    gl_Position = vec4 (vp * view_scale + view_translation, 0.0, 1.0);
+}
@@ -0,0 +1,56 @@
+#version 420 core
+
+in vec2 st;
+uniform sampler2D tex;
+uniform vec4 text_color;
+uniform bool msdf;
+uniform float threshold;
+uniform float softness;
+uniform vec4 outline_color;
+uniform float outline_width;
+uniform vec4 glow_color;
+uniform float glow_width;
+uniform bool premultiplied;
+out vec4 frag_color;
+
+
+float median (float r, float g, float b) {
+    return max (min (r, g), min (max (r, g), b));
+}
+
+vec4 blend_over (vec4 top, vec4 bottom) {
+    float alpha = top.a + bottom.a * (1.0 - top.a);
+    vec3 rgb = top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a);
+    return vec4 (rgb / max (alpha, 1e-4), alpha);
+}
+
+void main () {
+    vec4 texel = texture (tex, st);
+    float dist = msdf ? median (texel.r, texel.g, texel.b) : texel.a;
+
+    // The change in distance across one screen pixel. Widths are given in pixels
+    // so the edges stay the same on screen at any scale.
+    float px = max (fwidth (dist), 1e-4);
+    float aa = px * softness;
+
+    float fill = smoothstep (threshold - aa, threshold + aa, dist);
+    vec4 color = vec4 (text_color.rgb, text_color.a * fill);
+
+    float edge = threshold;
+    if (outline_width > 0.0) {
+        edge = threshold - outline_width * px;
+        float outline = smoothstep (edge - aa, edge + aa, dist);
+        color = mix (vec4 (outline_color.rgb, outline_color.a * outline), text_color, fill);
+    }
+
+    if (glow_width > 0.0) {
+        float glow = smoothstep (edge - glow_width * px, edge, dist);
+        color = blend_over (color, vec4 (glow_color.rgb, glow_color.a * glow));
+    }
+
+    if (premultiplied) {
+        color.rgb *= color.a;
+    }
+
+    frag_color = color;
+}
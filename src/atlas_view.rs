@@ -0,0 +1,143 @@
+use crate::color::Color;
+use crate::lines::LineBatch;
+
+
+/// The fraction of the window the atlas image fills when the inspector opens.
+const ATLAS_VIEW_FILL: f32 = 0.95;
+
+
+/// The placement of the atlas image in the atlas inspector. The image is centered
+/// in the window and scaled to fit while keeping its aspect ratio.
+#[derive(Copy, Clone, Debug)]
+pub struct AtlasLayout {
+    atlas_width: f32,
+    atlas_height: f32,
+    half_width: f32,
+    half_height: f32,
+}
+
+impl AtlasLayout {
+    pub fn new(atlas: &bmfa::BitmapFontAtlas, window_width: u32, window_height: u32) -> AtlasLayout {
+        let atlas_width = atlas.width as f32;
+        let atlas_height = atlas.height as f32;
+        let fit = ATLAS_VIEW_FILL * f32::min(
+            (window_width as f32) / atlas_width, (window_height as f32) / atlas_height
+        );
+
+        AtlasLayout {
+            atlas_width: atlas_width,
+            atlas_height: atlas_height,
+            half_width: fit * atlas_width / (window_width as f32),
+            half_height: fit * atlas_height / (window_height as f32),
+        }
+    }
+
+    /// Map a point in atlas pixels, measured from the top left corner of the atlas
+    /// image, to normalized device coordinates.
+    pub fn to_ndc(&self, x: f32, y: f32) -> (f32, f32) {
+        let ndc_x = -self.half_width + 2.0 * self.half_width * x / self.atlas_width;
+        let ndc_y = self.half_height - 2.0 * self.half_height * y / self.atlas_height;
+
+        (ndc_x, ndc_y)
+    }
+
+    /// The vertices and texture coordinates of the quad showing the whole atlas image.
+    /// The atlas image is stored bottom row first, so the top of the quad samples `t = 1`.
+    pub fn quad(&self) -> (Vec<f32>, Vec<f32>) {
+        let (left, top) = (-self.half_width, self.half_height);
+        let (right, bottom) = (self.half_width, -self.half_height);
+        let points = vec![
+            left, top, left, bottom, right, bottom,
+            right, bottom, right, top, left, top,
+        ];
+        let texcoords = vec![
+            0.0, 1.0, 0.0, 0.0, 1.0, 0.0,
+            1.0, 0.0, 1.0, 1.0, 0.0, 1.0,
+        ];
+
+        (points, texcoords)
+    }
+}
+
+/// The colors of the atlas inspector overlay.
+#[derive(Copy, Clone, Debug)]
+pub struct AtlasOverlayColors {
+    pub grid: Color,
+    pub padding: Color,
+    pub advance_box: Color,
+    pub y_offset: Color,
+}
+
+impl AtlasOverlayColors {
+    pub fn new() -> AtlasOverlayColors {
+        AtlasOverlayColors {
+            grid: Color::new(1.0, 1.0, 1.0, 0.35),
+            padding: Color::new(0.0, 1.0, 1.0, 0.35),
+            advance_box: Color::new(1.0, 0.0, 1.0, 0.9),
+            y_offset: Color::new(0.0, 1.0, 0.0, 0.9),
+        }
+    }
+
+    /// Apply a color conversion to every overlay color.
+    pub fn map<F: Fn(Color) -> Color>(self, f: F) -> AtlasOverlayColors {
+        AtlasOverlayColors {
+            grid: f(self.grid),
+            padding: f(self.padding),
+            advance_box: f(self.advance_box),
+            y_offset: f(self.y_offset),
+        }
+    }
+}
+
+/// Build the atlas inspector overlay: the `rows` by `columns` cell grid, the padding
+/// of each occupied cell, each glyph's advance box from its `x_min`, `width`
+/// and `height` metadata, and the text line top that the glyph's `y_offset` positions
+/// it against. Glyphs are drawn from the top left corner of their cell, and the padding
+/// is the band along the right and bottom edges that separates them from the next cells.
+/// The `width` of a glyph is its advance rather than the width of its ink.
+pub fn atlas_overlay(
+    atlas: &bmfa::BitmapFontAtlas, layout: &AtlasLayout,
+    colors: &AtlasOverlayColors, batch: &mut LineBatch) {
+
+    let slot = atlas.slot_glyph_size as f32;
+    let padding = atlas.padding as f32;
+    let grid_width = (atlas.columns as f32) * slot;
+    let grid_height = (atlas.rows as f32) * slot;
+
+    for column in 0..(atlas.columns + 1) {
+        let x = (column as f32) * slot;
+        let (x0, y0) = layout.to_ndc(x, 0.0);
+        let (x1, y1) = layout.to_ndc(x, grid_height);
+        batch.push_line(x0, y0, x1, y1, colors.grid);
+    }
+    for row in 0..(atlas.rows + 1) {
+        let y = (row as f32) * slot;
+        let (x0, y0) = layout.to_ndc(0.0, y);
+        let (x1, y1) = layout.to_ndc(grid_width, y);
+        batch.push_line(x0, y0, x1, y1, colors.grid);
+    }
+
+    for metadata in atlas.glyph_metadata.values() {
+        let cell_left = (metadata.x_min * (atlas.width as f32)).round();
+        let cell_top = (metadata.row as f32) * slot;
+
+        let (cell_right, cell_bottom) = (cell_left + slot, cell_top + slot);
+        let (x0, y0) = layout.to_ndc(cell_right - padding, cell_top);
+        let (x1, y1) = layout.to_ndc(cell_right, cell_bottom);
+        batch.push_filled_rect(x0, y0, x1, y1, colors.padding);
+        let (x0, y0) = layout.to_ndc(cell_left, cell_bottom - padding);
+        let (x1, y1) = layout.to_ndc(cell_right - padding, cell_bottom);
+        batch.push_filled_rect(x0, y0, x1, y1, colors.padding);
+
+        let box_right = cell_left + metadata.width * slot;
+        let box_bottom = cell_top + metadata.height * slot;
+        let (x0, y0) = layout.to_ndc(cell_left, cell_top);
+        let (x1, y1) = layout.to_ndc(box_right, box_bottom);
+        batch.push_rect(x0, y0, x1, y1, colors.advance_box);
+
+        let line_top = cell_top - metadata.y_offset * slot;
+        let (x0, y0) = layout.to_ndc(cell_left, line_top);
+        let (x1, y1) = layout.to_ndc(box_right, line_top);
+        batch.push_line(x0, y0, x1, y1, colors.y_offset);
+    }
+}
@@ -0,0 +1,18 @@
+use crate::color::Color;
+use crate::panel;
+use crate::panel::{Anchor, Panel};
+
+
+/// The size of the text in a banner.
+const BANNER_SCALE_PX: f32 = 32.0;
+
+
+/// Build a banner showing a message across the bottom of a window `width` by `height`
+/// pixels in size. Long messages wrap at the right edge of the window, and the banner
+/// grows upwards to fit them.
+pub fn banner(
+    atlas: &bmfa::BitmapFontAtlas, message: &str,
+    width: u32, height: u32, background: Color) -> Panel {
+
+    panel::panel(atlas, message, BANNER_SCALE_PX, Anchor::Bottom, width, height, background)
+}
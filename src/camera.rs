@@ -0,0 +1,114 @@
+/// The smallest and largest zoom factors a camera allows.
+const MIN_ZOOM: f32 = 0.125;
+const MAX_ZOOM: f32 = 64.0;
+
+
+/// A two dimensional view transform applied in the vertex shader. A point `p` in
+/// the scene, given in normalized device coordinates, is drawn on the screen at
+/// `p * zoom + pan`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    pub zoom: f32,
+    pub pan_x: f32,
+    pub pan_y: f32,
+}
+
+impl Camera {
+    /// A camera that leaves the scene unchanged.
+    pub fn new() -> Camera {
+        Camera {
+            zoom: 1.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Camera::new();
+    }
+
+    /// Map a point on the screen back to the scene.
+    pub fn to_scene(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.pan_x) / self.zoom, (y - self.pan_y) / self.zoom)
+    }
+
+    /// Scale the zoom by `factor` while keeping the scene point under the screen
+    /// point `(x, y)` fixed.
+    pub fn zoom_at(&mut self, x: f32, y: f32, factor: f32) {
+        let (scene_x, scene_y) = self.to_scene(x, y);
+        self.zoom = f32::min(f32::max(self.zoom * factor, MIN_ZOOM), MAX_ZOOM);
+        self.pan_x = x - scene_x * self.zoom;
+        self.pan_y = y - scene_y * self.zoom;
+    }
+
+    /// Move the view by an offset given in normalized device coordinates.
+    pub fn pan_by(&mut self, dx: f32, dy: f32) {
+        self.pan_x += dx;
+        self.pan_y += dy;
+    }
+}
+
+/// Convert a cursor position in window screen coordinates into normalized device
+/// coordinates. Screen coordinates are used rather than framebuffer pixels so this
+/// works the same on high DPI displays.
+pub fn cursor_to_ndc(window: &glfw::Window, cursor_x: f64, cursor_y: f64) -> (f32, f32) {
+    let (width, height) = window.get_size();
+    let x = 2.0 * (cursor_x as f32) / (width as f32) - 1.0;
+    let y = 1.0 - 2.0 * (cursor_y as f32) / (height as f32);
+
+    (x, y)
+}
+
+/// Mouse wheel zoom and click-drag panning for a camera.
+#[derive(Copy, Clone, Debug)]
+pub struct CameraController {
+    dragging: bool,
+    last_x: f32,
+    last_y: f32,
+}
+
+impl CameraController {
+    pub fn new() -> CameraController {
+        CameraController {
+            dragging: false,
+            last_x: 0.0,
+            last_y: 0.0,
+        }
+    }
+
+    /// Update the camera from a window event. Returns `true` if the camera changed.
+    pub fn handle_event(&mut self, camera: &mut Camera, window: &glfw::Window, event: &glfw::WindowEvent) -> bool {
+        match *event {
+            glfw::WindowEvent::Scroll(_, y_offset) => {
+                let (cursor_x, cursor_y) = window.get_cursor_pos();
+                let (x, y) = cursor_to_ndc(window, cursor_x, cursor_y);
+                camera.zoom_at(x, y, f32::powf(1.1, y_offset as f32));
+                true
+            }
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                let (cursor_x, cursor_y) = window.get_cursor_pos();
+                let (x, y) = cursor_to_ndc(window, cursor_x, cursor_y);
+                self.dragging = true;
+                self.last_x = x;
+                self.last_y = y;
+                false
+            }
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Release, _) => {
+                self.dragging = false;
+                false
+            }
+            glfw::WindowEvent::CursorPos(cursor_x, cursor_y) if self.dragging => {
+                let (x, y) = cursor_to_ndc(window, cursor_x, cursor_y);
+                camera.pan_by(x - self.last_x, y - self.last_y);
+                self.last_x = x;
+                self.last_y = y;
+                true
+            }
+            glfw::WindowEvent::Key(glfw::Key::Home, _, glfw::Action::Press, _) => {
+                camera.reset();
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::error;
+use std::fmt;
+use std::str;
+
+
+#[derive(Clone, Debug)]
+pub struct ParseCharacterSetError {
+    input: String,
+}
+
+impl fmt::Display for ParseCharacterSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "Invalid character set `{}`. Expected one of: {}.",
+            self.input, CharacterSet::all().iter().map(|set| set.to_string()).collect::<Vec<_>>().join(", ")
+        )
+    }
+}
+
+impl error::Error for ParseCharacterSetError {}
+
+/// The letters of the basic Latin alphabet.
+const LATIN_LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+
+/// The upper case form of a letter, if it is a single character. Letters such as
+/// `ß` and `ΐ` become several characters in upper case, which an atlas has no
+/// single glyph for.
+fn upper_case(ch: char) -> Option<char> {
+    let mut upper = ch.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) => Some(upper),
+        _ => None,
+    }
+}
+
+/// A named set of characters an atlas can be required to cover. The language sets
+/// hold the letters of each language's alphabet in both cases, and leave out the
+/// digits and punctuation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CharacterSet {
+    /// The printable ASCII characters from space to tilde.
+    PrintableAscii,
+    /// Printable ASCII along with the printable characters of the Latin-1 Supplement.
+    Latin1,
+    English,
+    French,
+    German,
+    Spanish,
+    Italian,
+    Portuguese,
+    Polish,
+    Czech,
+    Turkish,
+    Russian,
+    Ukrainian,
+    Greek,
+    Vietnamese,
+}
+
+impl CharacterSet {
+    pub fn all() -> &'static [CharacterSet] {
+        &[
+            CharacterSet::PrintableAscii, CharacterSet::Latin1, CharacterSet::English,
+            CharacterSet::French, CharacterSet::German, CharacterSet::Spanish,
+            CharacterSet::Italian, CharacterSet::Portuguese, CharacterSet::Polish,
+            CharacterSet::Czech, CharacterSet::Turkish, CharacterSet::Russian,
+            CharacterSet::Ukrainian, CharacterSet::Greek, CharacterSet::Vietnamese,
+        ]
+    }
+
+    /// Whether a language is written in the Latin alphabet, and its letters in lower
+    /// case where the letter has a lower case form. Latin alphabets only list the
+    /// letters added to the basic Latin ones.
+    fn letters(self) -> (bool, &'static str) {
+        match self {
+            CharacterSet::PrintableAscii | CharacterSet::Latin1 => (false, ""),
+            CharacterSet::English => (true, ""),
+            CharacterSet::French => (true, "àâæçéèêëîïôœùûüÿ"),
+            CharacterSet::German => (true, "äöüß"),
+            CharacterSet::Spanish => (true, "áéíñóúü"),
+            CharacterSet::Italian => (true, "àèéìíîòóùú"),
+            CharacterSet::Portuguese => (true, "àáâãçéêíóôõú"),
+            CharacterSet::Polish => (true, "ąćęłńóśźż"),
+            CharacterSet::Czech => (true, "áčďéěíňóřšťúůýž"),
+            CharacterSet::Turkish => (true, "çğıİöşü"),
+            CharacterSet::Russian => (false, "абвгдеёжзийклмнопрстуфхцчшщъыьэюя"),
+            CharacterSet::Ukrainian => (false, "абвгґдеєжзиіїйклмнопрстуфхцчшщьюя"),
+            CharacterSet::Greek => (false, "αβγδεζηθικλμνξοπρσςτυφχψωάέήίόύώϊϋΐΰ"),
+            CharacterSet::Vietnamese => (
+                true,
+                "àáảãạăằắẳẵặâầấẩẫậđèéẻẽẹêềếểễệìíỉĩịòóỏõọôồốổỗộơờớởỡợùúủũụưừứửữựỳýỷỹỵ"
+            ),
+        }
+    }
+
+    /// The code points in the set in ascending order.
+    pub fn code_points(self) -> Vec<usize> {
+        match self {
+            CharacterSet::PrintableAscii => (0x20..=0x7E).collect(),
+            CharacterSet::Latin1 => (0x20..=0x7E).chain(0xA0..=0xFF).collect(),
+            _ => {
+                let (latin, letters) = self.letters();
+                let latin = if latin { LATIN_LETTERS } else { "" };
+                let mut code_points: Vec<usize> = latin.chars()
+                    .chain(letters.chars())
+                    .flat_map(|ch| Some(ch).into_iter().chain(upper_case(ch)))
+                    .map(|ch| ch as usize)
+                    .collect();
+                code_points.sort();
+                code_points.dedup();
+
+                code_points
+            }
+        }
+    }
+
+    /// The code points in the set the atlas has no glyph for.
+    pub fn missing(self, atlas: &bmfa::BitmapFontAtlas) -> Vec<usize> {
+        self.code_points().into_iter()
+            .filter(|code_point| !atlas.glyph_metadata.contains_key(code_point))
+            .collect()
+    }
+}
+
+impl fmt::Display for CharacterSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CharacterSet::PrintableAscii => write!(f, "printable-ascii"),
+            CharacterSet::Latin1 => write!(f, "latin-1"),
+            CharacterSet::English => write!(f, "english"),
+            CharacterSet::French => write!(f, "french"),
+            CharacterSet::German => write!(f, "german"),
+            CharacterSet::Spanish => write!(f, "spanish"),
+            CharacterSet::Italian => write!(f, "italian"),
+            CharacterSet::Portuguese => write!(f, "portuguese"),
+            CharacterSet::Polish => write!(f, "polish"),
+            CharacterSet::Czech => write!(f, "czech"),
+            CharacterSet::Turkish => write!(f, "turkish"),
+            CharacterSet::Russian => write!(f, "russian"),
+            CharacterSet::Ukrainian => write!(f, "ukrainian"),
+            CharacterSet::Greek => write!(f, "greek"),
+            CharacterSet::Vietnamese => write!(f, "vietnamese"),
+        }
+    }
+}
+
+impl str::FromStr for CharacterSet {
+    type Err = ParseCharacterSetError;
+
+    fn from_str(st: &str) -> Result<CharacterSet, ParseCharacterSetError> {
+        CharacterSet::all().iter()
+            .find(|set| set.to_string() == st)
+            .cloned()
+            .ok_or_else(|| ParseCharacterSetError { input: st.to_string() })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_character_sets() {
+        assert_eq!("german".parse::<CharacterSet>().unwrap(), CharacterSet::German);
+        assert_eq!("latin-1".parse::<CharacterSet>().unwrap(), CharacterSet::Latin1);
+        assert!("klingon".parse::<CharacterSet>().is_err());
+    }
+
+    #[test]
+    fn language_sets_hold_both_cases() {
+        let german = CharacterSet::German.code_points();
+
+        // The basic Latin letters, `äöü` in both cases, and `ß`, which has no single upper case letter.
+        assert_eq!(german.len(), 26 * 2 + 3 * 2 + 1);
+        for &ch in ['a', 'Z', 'ä', 'Ö', 'ß'].iter() {
+            assert!(german.contains(&(ch as usize)), "{} should be in the set", ch);
+        }
+        assert!(!german.contains(&('1' as usize)));
+        assert!(german.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn missing_characters() {
+        let glyphs: Vec<(usize, usize, usize)> = (0x20..=0x7E).map(|code_point| (code_point, 0, 0)).collect();
+        let atlas = crate::test_atlas::atlas(1, 1, 8, 2, &glyphs);
+
+        assert!(CharacterSet::PrintableAscii.missing(&atlas).is_empty());
+        assert_eq!(CharacterSet::German.missing(&atlas), vec![0xC4, 0xD6, 0xDC, 0xDF, 0xE4, 0xF6, 0xFC]);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::layout;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+
+/// A string in a text file with characters the atlas cannot draw.
+#[derive(Clone, Debug)]
+pub struct UnrenderableString {
+    pub path: PathBuf,
+    /// The line the string starts on, counting from one.
+    pub line: usize,
+    pub text: String,
+    pub missing: Vec<char>,
+}
+
+impl fmt::Display for UnrenderableString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let missing: Vec<String> = self.missing.iter()
+            .map(|&ch| format!("U+{:04X} '{}'", ch as u32, ch))
+            .collect();
+        write!(f, "{}:{}: {} in {:?}", self.path.display(), self.line, missing.join(", "), self.text)
+    }
+}
+
+/// Read a quoted string whose opening quote was just read from `chars`, decoding
+/// its C style escapes.
+fn quoted_string(chars: &mut Chars) -> String {
+    let mut st = String::new();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => break,
+            '\\' => {
+                let decoded = match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some(other) => other,
+                    None => break,
+                };
+                st.push(decoded);
+            }
+            _ => st.push(ch),
+        }
+    }
+
+    st
+}
+
+/// The quoted strings on a line in order.
+fn quoted_strings(line: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '"' {
+            strings.push(quoted_string(&mut chars));
+        }
+    }
+
+    strings
+}
+
+/// The strings in a plain text file: every line is a string.
+fn text_file_strings(contents: &str) -> Vec<(usize, String)> {
+    contents.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, String::from(line)))
+        .collect()
+}
+
+/// Collect the string values in a JSON value, leaving out the object keys, since they
+/// are identifiers rather than text to display.
+fn json_string_values(value: &serde_json::Value, strings: &mut Vec<String>) {
+    match *value {
+        serde_json::Value::String(ref st) => strings.push(st.clone()),
+        serde_json::Value::Array(ref values) => {
+            for value in values.iter() {
+                json_string_values(value, strings);
+            }
+        }
+        serde_json::Value::Object(ref map) => {
+            for value in map.values() {
+                json_string_values(value, strings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The string values in the source of a JSON document in order, each along with the
+/// line it is on. The document must already have been parsed, so every string in
+/// it is well formed.
+fn json_value_lines(contents: &str) -> Vec<(usize, String)> {
+    let mut strings = Vec::new();
+    let mut line = 1;
+    let mut chars = contents.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        if ch == '\n' {
+            line += 1;
+        }
+        if ch != '"' {
+            continue;
+        }
+
+        let mut end = contents.len();
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => {
+                    end = index + 1;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        // JSON strings cannot span lines, so the string is on the current line.
+        let string_line = line;
+        while let Some(&(_, ch)) = chars.peek() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            if ch == '\n' {
+                line += 1;
+            }
+            chars.next();
+        }
+
+        // A string followed by a colon is an object key.
+        let is_key = chars.peek().map_or(false, |&(_, ch)| ch == ':');
+        if !is_key {
+            if let Ok(st) = serde_json::from_str::<String>(&contents[start..end]) {
+                strings.push((string_line, st));
+            }
+        }
+    }
+
+    strings
+}
+
+/// The string values in a JSON file, in the order they appear in it. The parsed values
+/// do not say where they were in the file, so the line each one is on is found from the
+/// string tokens in the source.
+fn json_file_strings(contents: &str) -> io::Result<Vec<(usize, String)>> {
+    let document: serde_json::Value = serde_json::from_str(contents)?;
+    let mut values = Vec::new();
+    json_string_values(&document, &mut values);
+
+    let mut lines: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (line, st) in json_value_lines(contents) {
+        lines.entry(st).or_insert_with(VecDeque::new).push_back(line);
+    }
+    let mut strings: Vec<(usize, String)> = values.into_iter()
+        .map(|st| {
+            let line = lines.get_mut(&st).and_then(|lines| lines.pop_front()).unwrap_or(0);
+            (line, st)
+        })
+        .collect();
+    strings.sort_by_key(|&(line, _)| line);
+
+    Ok(strings)
+}
+
+/// The part of a PO file entry being read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PoField {
+    Msgid,
+    Msgstr,
+    /// The `msgstr` of the header entry, which holds the file's metadata.
+    Header,
+    Other,
+}
+
+/// The translated strings in a gettext PO file. The `msgid` strings are the untranslated
+/// source text, so only the `msgstr` strings are checked. A string can continue over the
+/// quoted lines that follow it, and is reported on the line it starts on.
+fn po_file_strings(contents: &str) -> Vec<(usize, String)> {
+    let joined = |line: &str| -> String { quoted_strings(line).concat() };
+    let mut strings: Vec<(usize, String)> = Vec::new();
+    let mut msgid = String::new();
+    let mut field = PoField::Other;
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with("msgid ") {
+            field = PoField::Msgid;
+            msgid = joined(line);
+        } else if line.starts_with("msgstr") {
+            // The header is the entry with an empty `msgid`.
+            if msgid.is_empty() {
+                field = PoField::Header;
+            } else {
+                field = PoField::Msgstr;
+                strings.push((index + 1, joined(line)));
+            }
+        } else if line.starts_with('"') {
+            match field {
+                PoField::Msgid => msgid.push_str(&joined(line)),
+                PoField::Msgstr => {
+                    if let Some(last) = strings.last_mut() {
+                        last.1.push_str(&joined(line));
+                    }
+                }
+                PoField::Header | PoField::Other => {}
+            }
+        } else {
+            field = PoField::Other;
+        }
+    }
+
+    strings
+}
+
+/// Find the strings in a text file that the atlas cannot draw. JSON and PO files are
+/// recognized by their extension, and any other file is read as plain text.
+pub fn check_file(atlas: &bmfa::BitmapFontAtlas, path: &Path) -> io::Result<Vec<UnrenderableString>> {
+    let contents = fs::read_to_string(path)?;
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase());
+    let strings = match extension.as_ref().map(|extension| extension.as_str()) {
+        Some("json") => json_file_strings(&contents)?,
+        Some("po") | Some("pot") => po_file_strings(&contents),
+        _ => text_file_strings(&contents),
+    };
+
+    let unrenderable = strings.into_iter()
+        .filter_map(|(line, text)| {
+            let mut missing: Vec<char> = layout::missing_glyphs(atlas, &text).into_iter().map(|(_, ch)| ch).collect();
+            missing.sort();
+            missing.dedup();
+            if missing.is_empty() {
+                return None;
+            }
+
+            Some(UnrenderableString {
+                path: path.to_path_buf(),
+                line: line,
+                text: text,
+                missing: missing,
+            })
+        })
+        .collect();
+
+    Ok(unrenderable)
+}
+
+/// Summarize the characters missing from the atlas over all the strings checked,
+/// with the number of strings each one appears in, in code point order.
+pub fn summary(unrenderable: &[UnrenderableString]) -> String {
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for string in unrenderable.iter() {
+        for &ch in string.missing.iter() {
+            *counts.entry(ch).or_insert(0) += 1;
+        }
+    }
+
+    let mut summary = format!(
+        "{} strings contain {} characters the atlas has no glyph for:",
+        unrenderable.len(), counts.len()
+    );
+    for (ch, count) in counts {
+        write!(summary, "\n  U+{:04X} '{}' in {} strings", ch as u32, ch, count).unwrap();
+    }
+
+    summary
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_escapes() {
+        let line = r#"msgstr "tab\there \"quoted\" back\\slash\n" "second""#;
+
+        assert_eq!(quoted_strings(line), vec![
+            String::from("tab\there \"quoted\" back\\slash\n"),
+            String::from("second"),
+        ]);
+    }
+
+    #[test]
+    fn json_string_values_with_their_lines() {
+        let json = r#"{
+  "menu": {
+    "start": "Démarrer",
+    "quit":
+      "Quitter \"maintenant\"",
+    "say \"hi\":": "Salut"
+  },
+  "list": ["un", "deuxé"],
+  "count": 3,
+  "emoji": "😀"
+}"#;
+
+        assert_eq!(json_file_strings(json).unwrap(), vec![
+            (3, String::from("Démarrer")),
+            (5, String::from("Quitter \"maintenant\"")),
+            (6, String::from("Salut")),
+            (8, String::from("un")),
+            (8, String::from("deuxé")),
+            (10, String::from("\u{1F600}")),
+        ]);
+    }
+
+    #[test]
+    fn reject_malformed_json() {
+        assert!(json_file_strings(r#"{"title": "unterminated}"#).is_err());
+        assert!(json_file_strings(r#"{"title": "lone \ud83d surrogate"}"#).is_err());
+    }
+
+    #[test]
+    fn po_translated_strings() {
+        let po = r#"# A translation.
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+#: menu.c:10
+msgid "Start"
+msgstr "Démarrer"
+
+msgid ""
+"A long "
+"message"
+msgstr ""
+"Première ligne, "
+"deuxième \"ligne\"\n"
+
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] "Un fichier"
+msgstr[1] "%d fichiers"
+"#;
+
+        assert_eq!(po_file_strings(po), vec![
+            (8, String::from("Démarrer")),
+            (13, String::from("Première ligne, deuxième \"ligne\"\n")),
+            (19, String::from("Un fichier")),
+            (20, String::from("%d fichiers")),
+        ]);
+    }
+}
@@ -0,0 +1,207 @@
+use std::error;
+use std::fmt;
+use std::str;
+
+
+/// An RGBA color with each channel in the range `[0, 1]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r: r, g: g, b: b, a: a }
+    }
+
+    /// Convert a color with sRGB encoded channels to linear channels. The alpha
+    /// channel is always linear.
+    pub fn to_linear(self) -> Color {
+        Color::new(srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a)
+    }
+
+    /// Multiply the color channels by the alpha channel.
+    pub fn premultiplied(self) -> Color {
+        Color::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+}
+
+/// The text colors the viewer cycles through. The first one is the default.
+pub const TEXT_COLOR_PRESETS: [Color; 5] = [
+    Color::new(1.0, 1.0, 0.0, 1.0),
+    Color::new(1.0, 1.0, 1.0, 1.0),
+    Color::new(0.0, 0.0, 0.0, 1.0),
+    Color::new(1.0, 0.25, 0.25, 1.0),
+    Color::new(0.25, 1.0, 0.5, 1.0),
+];
+
+/// The background colors the viewer cycles through. The first one is the default.
+pub const BACKGROUND_COLOR_PRESETS: [Color; 5] = [
+    Color::new(0.2, 0.2, 0.6, 1.0),
+    Color::new(0.0, 0.0, 0.0, 1.0),
+    Color::new(1.0, 1.0, 1.0, 1.0),
+    Color::new(0.5, 0.5, 0.5, 1.0),
+    Color::new(0.1, 0.3, 0.1, 1.0),
+];
+
+/// Decode an sRGB encoded channel value into linear space.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear channel value into sRGB space.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParseColorError {
+    input: String,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid color `{}`. Expected a hex color `#RRGGBB` or `#RRGGBBAA`, \
+            a comma separated list `r,g,b[,a]` with channels in the range [0, 1], \
+            or a color name such as white or transparent.",
+            self.input
+        )
+    }
+}
+
+impl error::Error for ParseColorError {}
+
+fn parse_hex(st: &str) -> Option<Color> {
+    if !(st.len() == 6 || st.len() == 8) || !st.is_ascii() {
+        return None;
+    }
+
+    let mut channels = [1.0; 4];
+    for i in 0..(st.len() / 2) {
+        let byte = u8::from_str_radix(&st[2 * i..2 * i + 2], 16).ok()?;
+        channels[i] = byte as f32 / 255.0;
+    }
+
+    Some(Color::new(channels[0], channels[1], channels[2], channels[3]))
+}
+
+fn parse_name(st: &str) -> Option<Color> {
+    match st.to_ascii_lowercase().as_str() {
+        "black" => Some(Color::new(0.0, 0.0, 0.0, 1.0)),
+        "white" => Some(Color::new(1.0, 1.0, 1.0, 1.0)),
+        "red" => Some(Color::new(1.0, 0.0, 0.0, 1.0)),
+        "green" => Some(Color::new(0.0, 1.0, 0.0, 1.0)),
+        "blue" => Some(Color::new(0.0, 0.0, 1.0, 1.0)),
+        "yellow" => Some(Color::new(1.0, 1.0, 0.0, 1.0)),
+        "cyan" => Some(Color::new(0.0, 1.0, 1.0, 1.0)),
+        "magenta" => Some(Color::new(1.0, 0.0, 1.0, 1.0)),
+        "gray" | "grey" => Some(Color::new(0.5, 0.5, 0.5, 1.0)),
+        "transparent" => Some(Color::new(0.0, 0.0, 0.0, 0.0)),
+        _ => None,
+    }
+}
+
+fn parse_list(st: &str) -> Option<Color> {
+    let mut channels = [1.0; 4];
+    let mut count = 0;
+    for part in st.split(',') {
+        if count >= 4 {
+            return None;
+        }
+        let value = part.trim().parse::<f32>().ok()?;
+        if !(value >= 0.0 && value <= 1.0) {
+            return None;
+        }
+        channels[count] = value;
+        count += 1;
+    }
+
+    if count < 3 {
+        return None;
+    }
+
+    Some(Color::new(channels[0], channels[1], channels[2], channels[3]))
+}
+
+impl str::FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(st: &str) -> Result<Color, ParseColorError> {
+        let trimmed = st.trim();
+        let parsed = if trimmed.starts_with('#') {
+            parse_hex(&trimmed[1..])
+        } else if trimmed.contains(',') {
+            parse_list(trimmed)
+        } else {
+            parse_name(trimmed).or_else(|| parse_hex(trimmed))
+        };
+
+        parsed.ok_or_else(|| ParseColorError { input: st.to_string() })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_colors() {
+        assert_eq!("#FF000080".parse::<Color>().unwrap(), Color::new(1.0, 0.0, 0.0, 128.0 / 255.0));
+        assert_eq!("00ff00".parse::<Color>().unwrap(), Color::new(0.0, 1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn parse_named_colors() {
+        assert_eq!("white".parse::<Color>().unwrap(), Color::new(1.0, 1.0, 1.0, 1.0));
+        assert_eq!(" Grey ".parse::<Color>().unwrap(), Color::new(0.5, 0.5, 0.5, 1.0));
+        assert_eq!("transparent".parse::<Color>().unwrap(), Color::new(0.0, 0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn parse_channel_lists() {
+        assert_eq!("0.25, 0.5, 1".parse::<Color>().unwrap(), Color::new(0.25, 0.5, 1.0, 1.0));
+        assert_eq!("0,0,0,0.5".parse::<Color>().unwrap(), Color::new(0.0, 0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn reject_bad_colors() {
+        for input in &["", "#FFF", "#GGGGGG", "#FF0000FF00", "purple", "1,1", "1,1,1,1,1", "0,0,2", "0,NaN,0"] {
+            assert!(input.parse::<Color>().is_err(), "{} should not parse", input);
+        }
+    }
+
+    #[test]
+    fn srgb_round_trips() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert_eq!(linear_to_srgb(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+        assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_to_linear(0.5) - 0.214_041).abs() < 1e-5);
+        assert!((linear_to_srgb(0.214_041) - 0.5).abs() < 1e-5);
+        for &value in &[0.0, 0.02, 0.5, 1.0] {
+            assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5);
+            assert!((srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn premultiplied_color() {
+        let color = Color::new(1.0, 0.5, 0.0, 0.5).premultiplied();
+
+        assert_eq!(color, Color::new(0.5, 0.25, 0.0, 0.5));
+    }
+}
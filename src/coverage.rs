@@ -0,0 +1,210 @@
+use crate::charset::CharacterSet;
+
+use std::fmt::Write;
+
+
+/// The most missing characters listed for a block or character set before the rest
+/// are only counted.
+const MAX_LISTED_MISSING: usize = 32;
+
+
+/// The Unicode blocks coverage is reported for, as `(name, first, last)` code points.
+/// These are the blocks of the Basic Multilingual Plane that bitmapped fonts for games
+/// commonly draw from.
+pub const UNICODE_BLOCKS: &[(&str, usize, usize)] = &[
+    ("Basic Latin", 0x0000, 0x007F),
+    ("Latin-1 Supplement", 0x0080, 0x00FF),
+    ("Latin Extended-A", 0x0100, 0x017F),
+    ("Latin Extended-B", 0x0180, 0x024F),
+    ("IPA Extensions", 0x0250, 0x02AF),
+    ("Spacing Modifier Letters", 0x02B0, 0x02FF),
+    ("Combining Diacritical Marks", 0x0300, 0x036F),
+    ("Greek and Coptic", 0x0370, 0x03FF),
+    ("Cyrillic", 0x0400, 0x04FF),
+    ("Cyrillic Supplement", 0x0500, 0x052F),
+    ("Armenian", 0x0530, 0x058F),
+    ("Hebrew", 0x0590, 0x05FF),
+    ("Arabic", 0x0600, 0x06FF),
+    ("Devanagari", 0x0900, 0x097F),
+    ("Thai", 0x0E00, 0x0E7F),
+    ("Georgian", 0x10A0, 0x10FF),
+    ("Latin Extended Additional", 0x1E00, 0x1EFF),
+    ("Greek Extended", 0x1F00, 0x1FFF),
+    ("General Punctuation", 0x2000, 0x206F),
+    ("Superscripts and Subscripts", 0x2070, 0x209F),
+    ("Currency Symbols", 0x20A0, 0x20CF),
+    ("Letterlike Symbols", 0x2100, 0x214F),
+    ("Number Forms", 0x2150, 0x218F),
+    ("Arrows", 0x2190, 0x21FF),
+    ("Mathematical Operators", 0x2200, 0x22FF),
+    ("Miscellaneous Technical", 0x2300, 0x23FF),
+    ("Box Drawing", 0x2500, 0x257F),
+    ("Block Elements", 0x2580, 0x259F),
+    ("Geometric Shapes", 0x25A0, 0x25FF),
+    ("Miscellaneous Symbols", 0x2600, 0x26FF),
+    ("Dingbats", 0x2700, 0x27BF),
+    ("CJK Symbols and Punctuation", 0x3000, 0x303F),
+    ("Hiragana", 0x3040, 0x309F),
+    ("Katakana", 0x30A0, 0x30FF),
+    ("CJK Unified Ideographs", 0x4E00, 0x9FFF),
+    ("Hangul Syllables", 0xAC00, 0xD7AF),
+    ("Halfwidth and Fullwidth Forms", 0xFF00, 0xFFEF),
+];
+
+/// How much of a block or character set an atlas covers.
+#[derive(Clone, Debug)]
+pub struct Coverage {
+    pub name: String,
+    pub total: usize,
+    pub missing: Vec<usize>,
+}
+
+impl Coverage {
+    fn new(name: &str, code_points: &[usize], atlas: &bmfa::BitmapFontAtlas) -> Coverage {
+        let missing = code_points.iter()
+            .cloned()
+            .filter(|code_point| !atlas.glyph_metadata.contains_key(code_point))
+            .collect();
+
+        Coverage {
+            name: String::from(name),
+            total: code_points.len(),
+            missing: missing,
+        }
+    }
+
+    pub fn covered(&self) -> usize {
+        self.total - self.missing.len()
+    }
+
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            return 100.0;
+        }
+
+        100.0 * (self.covered() as f32) / (self.total as f32)
+    }
+}
+
+/// Whether a code point in a block is a character a font would draw. Control characters
+/// have no glyph. The blocks are not checked against the Unicode character database, so
+/// the few code points in them Unicode has not assigned yet still count.
+fn drawable(code_point: usize) -> bool {
+    std::char::from_u32(code_point as u32).map_or(false, |ch| !ch.is_control())
+}
+
+/// The coverage of each Unicode block. Blocks the atlas has no glyphs in are left out
+/// unless `all_blocks` is set.
+pub fn block_coverage(atlas: &bmfa::BitmapFontAtlas, all_blocks: bool) -> Vec<Coverage> {
+    UNICODE_BLOCKS.iter()
+        .map(|&(name, first, last)| {
+            let code_points: Vec<usize> = (first..=last).filter(|&code_point| drawable(code_point)).collect();
+            Coverage::new(name, &code_points, atlas)
+        })
+        .filter(|coverage| all_blocks || coverage.covered() > 0)
+        .collect()
+}
+
+/// The coverage of each predefined character set.
+pub fn set_coverage(atlas: &bmfa::BitmapFontAtlas) -> Vec<Coverage> {
+    CharacterSet::all().iter()
+        .map(|set| Coverage::new(&set.to_string(), &set.code_points(), atlas))
+        .collect()
+}
+
+/// List the characters missing from a block or character set, up to a limit.
+fn missing_description(missing: &[usize]) -> String {
+    let mut description = missing.iter()
+        .take(MAX_LISTED_MISSING)
+        .map(|&code_point| {
+            let ch = std::char::from_u32(code_point as u32).unwrap_or(std::char::REPLACEMENT_CHARACTER);
+            format!("U+{:04X} '{}'", code_point, ch)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if missing.len() > MAX_LISTED_MISSING {
+        write!(description, " and {} more", missing.len() - MAX_LISTED_MISSING).unwrap();
+    }
+
+    description
+}
+
+fn write_coverage(report: &mut String, coverage: &[Coverage]) {
+    let name_width = coverage.iter().map(|coverage| coverage.name.chars().count()).max().unwrap_or(0);
+    for coverage in coverage.iter() {
+        writeln!(
+            report, "  {:width$}  {:>5}/{:<5} {:5.1}%",
+            coverage.name, coverage.covered(), coverage.total, coverage.percent(), width = name_width
+        ).unwrap();
+        if !coverage.missing.is_empty() {
+            writeln!(report, "    missing: {}", missing_description(&coverage.missing)).unwrap();
+        }
+    }
+}
+
+/// Report how much of each Unicode block and character set the atlas covers, and
+/// which characters it is missing.
+pub fn report(atlas: &bmfa::BitmapFontAtlas, all_blocks: bool) -> String {
+    let mut report = String::new();
+    writeln!(report, "Unicode blocks:").unwrap();
+    write_coverage(&mut report, &block_coverage(atlas, all_blocks));
+    writeln!(report, "Character sets:").unwrap();
+    write_coverage(&mut report, &set_coverage(atlas));
+
+    report
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_atlas;
+
+    /// An atlas with every printable ASCII character but the tilde.
+    fn ascii_atlas() -> bmfa::BitmapFontAtlas {
+        let glyphs: Vec<(usize, usize, usize)> = (0x20..0x7E).map(|code_point| (code_point, 0, 0)).collect();
+
+        test_atlas::atlas(1, 1, 8, 2, &glyphs)
+    }
+
+    #[test]
+    fn unicode_block_coverage() {
+        let blocks = block_coverage(&ascii_atlas(), false);
+
+        // Only blocks with glyphs are listed, and control characters are left out of them.
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].name, "Basic Latin");
+        assert_eq!((blocks[0].covered(), blocks[0].total), (94, 95));
+        assert_eq!(blocks[0].missing, vec![0x7E]);
+        assert!((blocks[0].percent() - 100.0 * 94.0 / 95.0).abs() < 1e-4);
+        assert_eq!(block_coverage(&ascii_atlas(), true).len(), UNICODE_BLOCKS.len());
+    }
+
+    #[test]
+    fn language_coverage() {
+        let sets = set_coverage(&ascii_atlas());
+        let german = sets.iter().find(|coverage| coverage.name == "german").unwrap();
+
+        assert_eq!((german.covered(), german.total), (52, 59));
+        assert_eq!(german.missing, vec![0xC4, 0xD6, 0xDC, 0xDF, 0xE4, 0xF6, 0xFC]);
+    }
+
+    #[test]
+    fn report_lists_missing_characters() {
+        let report = report(&ascii_atlas(), false);
+
+        assert!(report.contains("    missing: U+007E '~'\n"));
+        assert!(report.contains(
+            "    missing: U+00C4 'Ä', U+00D6 'Ö', U+00DC 'Ü', U+00DF 'ß', U+00E4 'ä', U+00F6 'ö', U+00FC 'ü'\n"
+        ));
+    }
+
+    #[test]
+    fn long_missing_lists_are_cut_short() {
+        let missing: Vec<usize> = (0x41..0x41 + MAX_LISTED_MISSING + 3).collect();
+        let description = missing_description(&missing);
+
+        assert!(description.starts_with("U+0041 'A', U+0042 'B'"));
+        assert!(description.ends_with(" and 3 more"));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::color::Color;
+use crate::layout::{PlacedGlyph, TextLayout, TextPlacement};
+use crate::lines::LineBatch;
+
+use std::fmt;
+
+
+/// Which debug overlays are drawn over the text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DebugOverlays {
+    pub baselines: bool,
+    pub quads: bool,
+    pub ink_boxes: bool,
+    pub advances: bool,
+    pub wrap_margin: bool,
+}
+
+impl DebugOverlays {
+    pub fn new(enabled: bool) -> DebugOverlays {
+        DebugOverlays {
+            baselines: enabled,
+            quads: enabled,
+            ink_boxes: enabled,
+            advances: enabled,
+            wrap_margin: enabled,
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.baselines || self.quads || self.ink_boxes || self.advances || self.wrap_margin
+    }
+}
+
+impl fmt::Display for DebugOverlays {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        write!(
+            f, "baselines: {}, quads: {}, ink boxes: {}, advances: {}, wrap margin: {}",
+            on_off(self.baselines), on_off(self.quads), on_off(self.ink_boxes),
+            on_off(self.advances), on_off(self.wrap_margin)
+        )
+    }
+}
+
+/// The colors of the debug overlays.
+#[derive(Copy, Clone, Debug)]
+pub struct DebugOverlayColors {
+    pub baseline: Color,
+    pub quad: Color,
+    pub ink_box: Color,
+    pub advance: Color,
+    pub wrap_margin: Color,
+}
+
+impl DebugOverlayColors {
+    pub fn new() -> DebugOverlayColors {
+        DebugOverlayColors {
+            baseline: Color::new(0.0, 1.0, 0.0, 0.9),
+            quad: Color::new(1.0, 1.0, 1.0, 0.3),
+            ink_box: Color::new(1.0, 0.0, 1.0, 0.9),
+            advance: Color::new(0.0, 1.0, 1.0, 0.9),
+            wrap_margin: Color::new(1.0, 0.3, 0.0, 0.9),
+        }
+    }
+
+    /// Apply a color conversion to every overlay color.
+    pub fn map<F: Fn(Color) -> Color>(self, f: F) -> DebugOverlayColors {
+        DebugOverlayColors {
+            baseline: f(self.baseline),
+            quad: f(self.quad),
+            ink_box: f(self.ink_box),
+            advance: f(self.advance),
+            wrap_margin: f(self.wrap_margin),
+        }
+    }
+}
+
+/// The box the glyph covers inside its quad, from the `width` and `height` in the
+/// atlas metadata, as `(left, top, right, bottom)`. Glyphs are drawn from the top left
+/// corner of their atlas slots, so the box starts at the top left of the quad.
+fn ink_box(
+    atlas: &bmfa::BitmapFontAtlas, glyph: &PlacedGlyph,
+    placement: &TextPlacement, width: u32, height: u32) -> (f32, f32, f32, f32) {
+
+    let metadata = atlas.glyph_metadata[&glyph.code_point];
+    let scale_x = placement.scale_px / (width as f32);
+    let scale_y = placement.scale_px / (height as f32);
+
+    let (left, top) = (glyph.left, glyph.top);
+    let right = left + metadata.width * scale_x;
+    let bottom = top - metadata.height * scale_y;
+
+    (left, top, right, bottom)
+}
+
+/// The median of a list of values, or `None` if it is empty.
+fn median(values: &mut [f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    Some(values[values.len() / 2])
+}
+
+/// Build the debug overlays for a block of laid out text. The atlas metadata has no
+/// explicit baseline, so each line's baseline is the median of the bottoms of its
+/// glyphs' ink boxes, which is where most glyphs on a line sit.
+pub fn text_overlay(
+    atlas: &bmfa::BitmapFontAtlas, text_layout: &TextLayout, placement: &TextPlacement,
+    width: u32, height: u32, overlays: &DebugOverlays,
+    colors: &DebugOverlayColors, batch: &mut LineBatch) {
+
+    for glyph in text_layout.glyphs.iter() {
+        if overlays.quads {
+            batch.push_rect(glyph.left, glyph.top, glyph.right, glyph.bottom, colors.quad);
+        }
+        if overlays.ink_boxes {
+            let (left, top, right, bottom) = ink_box(atlas, glyph, placement, width, height);
+            batch.push_rect(left, top, right, bottom, colors.ink_box);
+        }
+        if overlays.advances {
+            let pen_x = glyph.left + glyph.advance;
+            batch.push_line(pen_x, glyph.top, pen_x, glyph.bottom, colors.advance);
+        }
+    }
+
+    if overlays.baselines {
+        let line_count = text_layout.glyphs.last().map_or(0, |glyph| glyph.line + 1);
+        for line in 0..line_count {
+            let glyphs_on_line = text_layout.glyphs.iter().filter(|glyph| glyph.line == line);
+            let mut bottoms: Vec<f32> = glyphs_on_line.clone()
+                .map(|glyph| ink_box(atlas, glyph, placement, width, height).3)
+                .collect();
+            let left = glyphs_on_line.clone().map(|glyph| glyph.left).fold(std::f32::INFINITY, f32::min);
+            let right = glyphs_on_line.map(|glyph| glyph.left + glyph.advance).fold(std::f32::NEG_INFINITY, f32::max);
+            if let Some(baseline) = median(&mut bottoms) {
+                batch.push_line(left, baseline, right, baseline, colors.baseline);
+            }
+        }
+    }
+
+    if overlays.wrap_margin {
+        batch.push_line(placement.end_at_x, 1.0, placement.end_at_x, -1.0, colors.wrap_margin);
+    }
+}
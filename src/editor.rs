@@ -0,0 +1,163 @@
+use crate::color::Color;
+use crate::layout::{TextLayout, TextPlacement};
+use crate::lines::LineBatch;
+
+use glfw::{Action, Key};
+
+
+/// The width of the caret in pixels.
+const CARET_WIDTH_PX: f32 = 2.0;
+
+
+/// The text shown in the viewer, along with the caret used to edit it. The caret
+/// is the index of the character it sits in front of.
+#[derive(Clone, Debug)]
+pub struct TextEditor {
+    pub text: String,
+    pub caret: usize,
+    pub editing: bool,
+}
+
+impl TextEditor {
+    pub fn new(text: &str) -> TextEditor {
+        TextEditor {
+            text: String::from(text),
+            caret: text.chars().count(),
+            editing: false,
+        }
+    }
+
+    /// Replace the text, keeping the caret where it was if the new text is long enough.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = String::from(text);
+        self.caret = usize::min(self.caret, self.char_count());
+    }
+
+    /// The number of characters in the text.
+    fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// The byte offset in the text of a character index.
+    fn byte_offset(&self, index: usize) -> usize {
+        self.text.char_indices().nth(index).map_or(self.text.len(), |(offset, _)| offset)
+    }
+
+    fn insert(&mut self, ch: char) {
+        let offset = self.byte_offset(self.caret);
+        self.text.insert(offset, ch);
+        self.caret += 1;
+    }
+
+    fn delete_before_caret(&mut self) -> bool {
+        if self.caret == 0 {
+            return false;
+        }
+        self.caret -= 1;
+        let offset = self.byte_offset(self.caret);
+        self.text.remove(offset);
+
+        true
+    }
+
+    fn delete_after_caret(&mut self) -> bool {
+        if self.caret >= self.char_count() {
+            return false;
+        }
+        let offset = self.byte_offset(self.caret);
+        self.text.remove(offset);
+
+        true
+    }
+
+    /// The character index of the start of the line the caret is on.
+    fn line_start(&self) -> usize {
+        self.text.chars()
+            .take(self.caret)
+            .enumerate()
+            .filter(|&(_, ch)| ch == '\n')
+            .last()
+            .map_or(0, |(index, _)| index + 1)
+    }
+
+    /// The character index of the end of the line the caret is on.
+    fn line_end(&self) -> usize {
+        self.text.chars()
+            .enumerate()
+            .skip(self.caret)
+            .find(|&(_, ch)| ch == '\n')
+            .map_or(self.char_count(), |(index, _)| index)
+    }
+}
+
+/// What an event did to the text being edited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EditResult {
+    /// The event was not for the editor.
+    Ignored,
+    /// Only the caret moved.
+    CaretMoved,
+    /// The text changed and must be laid out again.
+    TextChanged,
+}
+
+/// Edit the text from a window event. Characters the atlas has no glyph for are not
+/// inserted, since there would be nothing to draw them with.
+pub fn update_text_editor(
+    editor: &mut TextEditor, atlas: &bmfa::BitmapFontAtlas, event: &glfw::WindowEvent) -> EditResult {
+
+    match *event {
+        glfw::WindowEvent::Char(ch) => {
+            if atlas.glyph_metadata.contains_key(&(ch as usize)) {
+                editor.insert(ch);
+                EditResult::TextChanged
+            } else {
+                EditResult::Ignored
+            }
+        }
+        glfw::WindowEvent::Key(key, _, Action::Press, _) |
+        glfw::WindowEvent::Key(key, _, Action::Repeat, _) => {
+            match key {
+                Key::Enter | Key::KpEnter => {
+                    editor.insert('\n');
+                    EditResult::TextChanged
+                }
+                Key::Backspace if editor.delete_before_caret() => EditResult::TextChanged,
+                Key::Delete if editor.delete_after_caret() => EditResult::TextChanged,
+                Key::Left if editor.caret > 0 => {
+                    editor.caret -= 1;
+                    EditResult::CaretMoved
+                }
+                Key::Right if editor.caret < editor.char_count() => {
+                    editor.caret += 1;
+                    EditResult::CaretMoved
+                }
+                Key::Home => {
+                    editor.caret = editor.line_start();
+                    EditResult::CaretMoved
+                }
+                Key::End => {
+                    editor.caret = editor.line_end();
+                    EditResult::CaretMoved
+                }
+                _ => EditResult::Ignored,
+            }
+        }
+        _ => EditResult::Ignored,
+    }
+}
+
+/// Build the caret for the laid out text. The caret runs from the top of the line
+/// down one glyph height, at the pen position in front of the caret's character.
+pub fn caret(
+    editor: &TextEditor, text_layout: &TextLayout, placement: &TextPlacement,
+    width: u32, height: u32, color: Color, batch: &mut LineBatch) {
+
+    let (x, y) = match text_layout.pen_positions.get(editor.caret) {
+        Some(&position) => position,
+        None => (placement.start_at_x, placement.start_at_y),
+    };
+    let caret_width = CARET_WIDTH_PX * 2.0 / (width as f32);
+    let caret_height = placement.scale_px / (height as f32);
+    batch.push_filled_rect(x, y, x + caret_width, y - caret_height, color);
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+
+/// How the glyph images in a font atlas are encoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlyphEncoding {
+    /// The alpha channel holds the glyph coverage.
+    Bitmap,
+    /// The alpha channel holds a signed distance field.
+    Sdf,
+    /// The color channels hold a multi-channel signed distance field.
+    Msdf,
+}
+
+impl fmt::Display for GlyphEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GlyphEncoding::Bitmap => write!(f, "bitmap"),
+            GlyphEncoding::Sdf => write!(f, "SDF"),
+            GlyphEncoding::Msdf => write!(f, "MSDF"),
+        }
+    }
+}
+
+/// Guess how the glyphs in a font atlas are encoded. The atlas metadata does not
+/// record this, so we look at the image instead. A coverage bitmap is mostly empty
+/// with partially transparent pixels only along the glyph edges, whereas a distance
+/// field spreads smooth gradients well past the edges. A multi-channel distance field
+/// also stores different values in each color channel.
+pub fn detect_glyph_encoding(atlas: &bmfa::BitmapFontAtlas) -> GlyphEncoding {
+    let mut total = 0;
+    let mut inked = 0;
+    let mut partial = 0;
+    let mut colored = 0;
+    for pixel in atlas.image.chunks(4) {
+        if pixel.len() < 4 {
+            break;
+        }
+        total += 1;
+
+        let (r, g, b, a) = (pixel[0], pixel[1], pixel[2], pixel[3]);
+        let max_rgb = u8::max(r, u8::max(g, b));
+        let min_rgb = u8::min(r, u8::min(g, b));
+        if max_rgb - min_rgb > 32 {
+            colored += 1;
+        }
+        if a > 0 {
+            inked += 1;
+            if a < 255 {
+                partial += 1;
+            }
+        }
+    }
+
+    if total == 0 {
+        return GlyphEncoding::Bitmap;
+    }
+    if (colored as f32) / (total as f32) > 0.1 {
+        return GlyphEncoding::Msdf;
+    }
+    if inked > 0 && (inked as f32) / (total as f32) > 0.3 && (partial as f32) / (inked as f32) > 0.6 {
+        return GlyphEncoding::Sdf;
+    }
+
+    GlyphEncoding::Bitmap
+}
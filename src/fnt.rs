@@ -0,0 +1,625 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str;
+
+
+/// The BMFont channel mask for a glyph drawn in every channel of the page.
+const ALL_CHANNELS: u8 = 15;
+
+
+#[derive(Clone, Debug)]
+pub struct ParseExportFormatError {
+    input: String,
+}
+
+impl fmt::Display for ParseExportFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid export format `{}`. Expected one of: fnt, fnt-xml, fnt-binary.", self.input)
+    }
+}
+
+impl error::Error for ParseExportFormatError {}
+
+/// The font formats an atlas can be exported to. These are the three variants of the
+/// AngelCode BMFont descriptor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The text descriptor, one tag per line.
+    FntText,
+    FntXml,
+    /// The binary descriptor, version 3.
+    FntBinary,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExportFormat::FntText => write!(f, "fnt"),
+            ExportFormat::FntXml => write!(f, "fnt-xml"),
+            ExportFormat::FntBinary => write!(f, "fnt-binary"),
+        }
+    }
+}
+
+impl str::FromStr for ExportFormat {
+    type Err = ParseExportFormatError;
+
+    fn from_str(st: &str) -> Result<ExportFormat, ParseExportFormatError> {
+        match st {
+            "fnt" => Ok(ExportFormat::FntText),
+            "fnt-xml" => Ok(ExportFormat::FntXml),
+            "fnt-binary" => Ok(ExportFormat::FntBinary),
+            _ => Err(ParseExportFormatError { input: st.to_string() }),
+        }
+    }
+}
+
+/// A glyph in a BMFont descriptor. The glyph's rectangle is in page pixels measured
+/// from the top left corner, and the offsets place it relative to the pen position
+/// at the top of the line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FntChar {
+    pub id: u32,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub x_advance: i32,
+    pub page: u32,
+}
+
+/// The parts of a BMFont descriptor that a font atlas fills in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FntFont {
+    pub face: String,
+    pub size: u32,
+    pub padding: u32,
+    pub line_height: u32,
+    /// The distance from the top of a line to the baseline.
+    pub base: u32,
+    pub scale_width: u32,
+    pub scale_height: u32,
+    /// The file names of the page images, relative to the descriptor.
+    pub pages: Vec<String>,
+    pub chars: Vec<FntChar>,
+}
+
+/// The median of a list of values, or zero if it is empty.
+fn median(values: &mut [i32]) -> i32 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort();
+
+    values[values.len() / 2]
+}
+
+/// Describe a font atlas as a BMFont descriptor with a single page. Glyphs are drawn
+/// from the top left corner of their cell, so each glyph's rectangle on the page is the
+/// part of its cell in front of the padding band along its right and bottom edges. The
+/// viewer places the whole cell at the pen with the top of the cell `y_offset` below the
+/// top of the line, and advances the pen by the glyph's `width`, so the offsets carry that
+/// over. The atlas has no baseline metric, so the base is the bottom of the ink most glyphs
+/// share, as in the viewer's debug overlays.
+pub fn fnt_from_atlas(atlas: &bmfa::BitmapFontAtlas, face: &str, page: &str) -> FntFont {
+    let slot = atlas.slot_glyph_size as f32;
+    let padding = atlas.padding as u32;
+    let size = u32::max(atlas.slot_glyph_size as u32, padding) - padding;
+
+    let mut code_points: Vec<usize> = atlas.glyph_metadata.keys().cloned().collect();
+    code_points.sort();
+    let chars: Vec<FntChar> = code_points.iter()
+        .map(|code_point| {
+            let metadata = &atlas.glyph_metadata[code_point];
+            FntChar {
+                id: *code_point as u32,
+                x: (metadata.column as u32) * (atlas.slot_glyph_size as u32),
+                y: (metadata.row as u32) * (atlas.slot_glyph_size as u32),
+                width: size,
+                height: size,
+                x_offset: 0,
+                y_offset: (metadata.y_offset * slot).round() as i32,
+                x_advance: (metadata.width * slot).round() as i32,
+                page: 0,
+            }
+        })
+        .collect();
+    let mut bottoms: Vec<i32> = code_points.iter()
+        .map(|code_point| {
+            let metadata = &atlas.glyph_metadata[code_point];
+            ((metadata.y_offset + metadata.height) * slot).round() as i32
+        })
+        .collect();
+    let base = median(&mut bottoms);
+
+    FntFont {
+        face: String::from(face),
+        size: atlas.glyph_size as u32,
+        padding: padding,
+        line_height: atlas.slot_glyph_size as u32,
+        base: i32::max(base, 0) as u32,
+        scale_width: atlas.width as u32,
+        scale_height: atlas.height as u32,
+        pages: vec![String::from(page)],
+        chars: chars,
+    }
+}
+
+/// The atlas image with its top row first, as the page image of a BMFont descriptor.
+/// The atlas stores its image bottom row first for OpenGL.
+pub fn page_image(atlas: &bmfa::BitmapFontAtlas) -> Vec<u8> {
+    let row_size = 4 * atlas.width as usize;
+    let mut image = Vec::with_capacity(atlas.image.len());
+    for row in atlas.image.chunks(row_size).rev() {
+        image.extend_from_slice(row);
+    }
+
+    image
+}
+
+/// The paths of the descriptor and page image for an export. The page is named after
+/// the descriptor, with the page number appended, as BMFont names its pages.
+pub fn export_paths(output_path: &Path) -> (PathBuf, PathBuf) {
+    let stem = output_path.file_stem().map_or(String::from("font"), |stem| stem.to_string_lossy().into_owned());
+
+    (output_path.to_path_buf(), output_path.with_file_name(format!("{}_0.png", stem)))
+}
+
+fn write_text<W: Write>(font: &FntFont, writer: &mut W) -> io::Result<()> {
+    let p = font.padding;
+    writeln!(
+        writer,
+        "info face=\"{}\" size={} bold=0 italic=0 charset=\"\" unicode=1 stretchH=100 smooth=1 aa=1 \
+         padding={},{},{},{} spacing=0,0 outline=0",
+        font.face, font.size, p, p, p, p
+    )?;
+    writeln!(
+        writer,
+        "common lineHeight={} base={} scaleW={} scaleH={} pages={} packed=0 \
+         alphaChnl=0 redChnl=4 greenChnl=4 blueChnl=4",
+        font.line_height, font.base, font.scale_width, font.scale_height, font.pages.len()
+    )?;
+    for (id, page) in font.pages.iter().enumerate() {
+        writeln!(writer, "page id={} file=\"{}\"", id, page)?;
+    }
+    writeln!(writer, "chars count={}", font.chars.len())?;
+    for ch in font.chars.iter() {
+        writeln!(
+            writer,
+            "char id={} x={} y={} width={} height={} xoffset={} yoffset={} xadvance={} page={} chnl={}",
+            ch.id, ch.x, ch.y, ch.width, ch.height, ch.x_offset, ch.y_offset, ch.x_advance, ch.page, ALL_CHANNELS
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Escape the characters that cannot appear in an XML attribute value.
+fn xml_escape(st: &str) -> String {
+    st.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn write_xml<W: Write>(font: &FntFont, writer: &mut W) -> io::Result<()> {
+    let p = font.padding;
+    writeln!(writer, "<?xml version=\"1.0\"?>")?;
+    writeln!(writer, "<font>")?;
+    writeln!(
+        writer,
+        "  <info face=\"{}\" size=\"{}\" bold=\"0\" italic=\"0\" charset=\"\" unicode=\"1\" stretchH=\"100\" \
+         smooth=\"1\" aa=\"1\" padding=\"{},{},{},{}\" spacing=\"0,0\" outline=\"0\"/>",
+        xml_escape(&font.face), font.size, p, p, p, p
+    )?;
+    writeln!(
+        writer,
+        "  <common lineHeight=\"{}\" base=\"{}\" scaleW=\"{}\" scaleH=\"{}\" pages=\"{}\" packed=\"0\" \
+         alphaChnl=\"0\" redChnl=\"4\" greenChnl=\"4\" blueChnl=\"4\"/>",
+        font.line_height, font.base, font.scale_width, font.scale_height, font.pages.len()
+    )?;
+    writeln!(writer, "  <pages>")?;
+    for (id, page) in font.pages.iter().enumerate() {
+        writeln!(writer, "    <page id=\"{}\" file=\"{}\"/>", id, xml_escape(page))?;
+    }
+    writeln!(writer, "  </pages>")?;
+    writeln!(writer, "  <chars count=\"{}\">", font.chars.len())?;
+    for ch in font.chars.iter() {
+        writeln!(
+            writer,
+            "    <char id=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" xoffset=\"{}\" yoffset=\"{}\" \
+             xadvance=\"{}\" page=\"{}\" chnl=\"{}\"/>",
+            ch.id, ch.x, ch.y, ch.width, ch.height, ch.x_offset, ch.y_offset, ch.x_advance, ch.page, ALL_CHANNELS
+        )?;
+    }
+    writeln!(writer, "  </chars>")?;
+    writeln!(writer, "</font>")
+}
+
+/// Write a block of the binary descriptor: its type, its size, and then its contents.
+fn write_binary_block<W: Write>(writer: &mut W, block_type: u8, contents: &[u8]) -> io::Result<()> {
+    writer.write_all(&[block_type])?;
+    writer.write_all(&(contents.len() as u32).to_le_bytes())?;
+    writer.write_all(contents)
+}
+
+fn write_binary<W: Write>(font: &FntFont, writer: &mut W) -> io::Result<()> {
+    writer.write_all(b"BMF\x03")?;
+
+    let mut info = Vec::new();
+    info.extend_from_slice(&(font.size as i16).to_le_bytes());
+    // Smooth and Unicode.
+    info.push(0b0000_0011);
+    info.push(0);
+    info.extend_from_slice(&100u16.to_le_bytes());
+    info.push(1);
+    info.extend_from_slice(&[font.padding as u8; 4]);
+    info.extend_from_slice(&[0, 0, 0]);
+    info.extend_from_slice(font.face.as_bytes());
+    info.push(0);
+    write_binary_block(writer, 1, &info)?;
+
+    let mut common = Vec::new();
+    for &value in [font.line_height, font.base, font.scale_width, font.scale_height, font.pages.len() as u32].iter() {
+        common.extend_from_slice(&(value as u16).to_le_bytes());
+    }
+    common.extend_from_slice(&[0, 0, 4, 4, 4]);
+    write_binary_block(writer, 2, &common)?;
+
+    let mut pages = Vec::new();
+    for page in font.pages.iter() {
+        pages.extend_from_slice(page.as_bytes());
+        pages.push(0);
+    }
+    write_binary_block(writer, 3, &pages)?;
+
+    let mut chars = Vec::with_capacity(20 * font.chars.len());
+    for ch in font.chars.iter() {
+        chars.extend_from_slice(&ch.id.to_le_bytes());
+        for &value in [ch.x, ch.y, ch.width, ch.height].iter() {
+            chars.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        for &value in [ch.x_offset, ch.y_offset, ch.x_advance].iter() {
+            chars.extend_from_slice(&(value as i16).to_le_bytes());
+        }
+        chars.push(ch.page as u8);
+        chars.push(ALL_CHANNELS);
+    }
+    write_binary_block(writer, 4, &chars)
+}
+
+/// Write a BMFont descriptor in one of its three variants.
+pub fn write_fnt<W: Write>(font: &FntFont, format: ExportFormat, writer: &mut W) -> io::Result<()> {
+    match format {
+        ExportFormat::FntText => write_text(font, writer),
+        ExportFormat::FntXml => write_xml(font, writer),
+        ExportFormat::FntBinary => write_binary(font, writer),
+    }
+}
+
+/// The most pages a descriptor can have. The binary descriptor stores the page of each
+/// character in a single byte.
+const MAX_PAGES: usize = 256;
+
+/// A tag of a text or XML descriptor, such as `char`, with its attributes.
+type FntTag = (String, HashMap<String, String>);
+
+/// Undo the escapes of an XML attribute value.
+fn xml_unescape(st: &str) -> String {
+    st.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Split a tag into its name and `key=value` attributes. Values can be quoted to hold
+/// spaces. This reads both the lines of a text descriptor and the elements of an XML
+/// descriptor, once the angle brackets are removed.
+fn parse_tag(st: &str, unescape: bool) -> Option<FntTag> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    for ch in st.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            ch if ch.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(token.clone());
+                    token.clear();
+                }
+            }
+            _ => token.push(ch),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    let mut tokens = tokens.into_iter();
+    let name = tokens.next()?;
+    let attributes = tokens
+        .filter_map(|token| {
+            let mut parts = token.splitn(2, '=');
+            let key = parts.next()?.to_string();
+            let value = parts.next().unwrap_or("");
+            let value = if unescape { xml_unescape(value) } else { value.to_string() };
+
+            Some((key, value))
+        })
+        .collect();
+
+    Some((name, attributes))
+}
+
+fn text_tags(contents: &str) -> Vec<FntTag> {
+    contents.lines().filter_map(|line| parse_tag(line, false)).collect()
+}
+
+fn xml_tags(contents: &str) -> Vec<FntTag> {
+    contents.split('<')
+        .filter_map(|element| {
+            let element = element.split('>').next()?.trim_end_matches('/');
+            if element.starts_with('?') || element.starts_with('!') || element.starts_with('/') {
+                return None;
+            }
+
+            parse_tag(element, true)
+        })
+        .collect()
+}
+
+/// Read a number attribute of a tag. Missing attributes are zero.
+fn attribute<T: str::FromStr + Default>(attributes: &HashMap<String, String>, key: &str) -> Result<T, String> {
+    match attributes.get(key) {
+        Some(value) => value.trim().parse().map_err(|_| format!("Invalid value `{}` for `{}`.", value, key)),
+        None => Ok(T::default()),
+    }
+}
+
+fn fnt_from_tags(tags: &[FntTag]) -> Result<FntFont, String> {
+    let mut font = FntFont::default();
+    // Pages can be listed in any order, so they are collected by id and checked for gaps
+    // once every tag has been read.
+    let mut pages: Vec<Option<String>> = Vec::new();
+    let mut page_count = MAX_PAGES;
+    for &(ref name, ref attributes) in tags.iter() {
+        match name.as_str() {
+            "info" => {
+                font.face = attributes.get("face").cloned().unwrap_or_default();
+                font.size = attribute::<i32>(attributes, "size")?.abs() as u32;
+                font.padding = attributes.get("padding")
+                    .and_then(|padding| padding.split(',').filter_map(|p| p.trim().parse().ok()).max())
+                    .unwrap_or(0);
+            }
+            "common" => {
+                font.line_height = attribute(attributes, "lineHeight")?;
+                font.base = attribute(attributes, "base")?;
+                font.scale_width = attribute(attributes, "scaleW")?;
+                font.scale_height = attribute(attributes, "scaleH")?;
+                if attributes.contains_key("pages") {
+                    page_count = usize::min(attribute(attributes, "pages")?, MAX_PAGES);
+                }
+            }
+            "page" => {
+                let id: usize = attribute(attributes, "id")?;
+                if id >= page_count {
+                    return Err(format!("The page id {} is not below the page count of {}.", id, page_count));
+                }
+                let file = attributes.get("file").cloned().ok_or_else(|| format!("The page {} has no file.", id))?;
+                if pages.len() <= id {
+                    pages.resize(id + 1, None);
+                }
+                pages[id] = Some(file);
+            }
+            "char" => {
+                font.chars.push(FntChar {
+                    id: attribute(attributes, "id")?,
+                    x: attribute(attributes, "x")?,
+                    y: attribute(attributes, "y")?,
+                    width: attribute(attributes, "width")?,
+                    height: attribute(attributes, "height")?,
+                    x_offset: attribute(attributes, "xoffset")?,
+                    y_offset: attribute(attributes, "yoffset")?,
+                    x_advance: attribute(attributes, "xadvance")?,
+                    page: attribute(attributes, "page")?,
+                });
+            }
+            _ => {}
+        }
+    }
+    for (id, page) in pages.into_iter().enumerate() {
+        font.pages.push(page.ok_or_else(|| format!("The page {} is missing.", id))?);
+    }
+
+    Ok(font)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    bytes.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| String::from("The binary font descriptor ended early."))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| String::from("The binary font descriptor ended early."))
+}
+
+/// The null terminated strings in a block of the binary descriptor.
+fn null_terminated_strings(bytes: &[u8]) -> Vec<String> {
+    bytes.split(|&byte| byte == 0)
+        .filter(|st| !st.is_empty())
+        .map(|st| String::from_utf8_lossy(st).into_owned())
+        .collect()
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<FntFont, String> {
+    if bytes.get(3) != Some(&3) {
+        return Err(String::from("Only version 3 of the binary font descriptor is supported."));
+    }
+    let mut font = FntFont::default();
+    let mut offset = 4;
+    while offset < bytes.len() {
+        let block_type = bytes[offset];
+        let size = read_u32(bytes, offset + 1)? as usize;
+        let block = bytes.get(offset + 5..offset + 5 + size)
+            .ok_or_else(|| String::from("The binary font descriptor ended early."))?;
+        match block_type {
+            1 => {
+                font.size = (read_u16(block, 0)? as i16).abs() as u32;
+                font.padding = block.get(7..11).and_then(|padding| padding.iter().max()).cloned().unwrap_or(0) as u32;
+                font.face = null_terminated_strings(block.get(14..).unwrap_or(&[])).into_iter().next().unwrap_or_default();
+            }
+            2 => {
+                font.line_height = read_u16(block, 0)? as u32;
+                font.base = read_u16(block, 2)? as u32;
+                font.scale_width = read_u16(block, 4)? as u32;
+                font.scale_height = read_u16(block, 6)? as u32;
+            }
+            3 => font.pages = null_terminated_strings(block),
+            4 => {
+                for ch in block.chunks(20).filter(|ch| ch.len() == 20) {
+                    font.chars.push(FntChar {
+                        id: read_u32(ch, 0)?,
+                        x: read_u16(ch, 4)? as u32,
+                        y: read_u16(ch, 6)? as u32,
+                        width: read_u16(ch, 8)? as u32,
+                        height: read_u16(ch, 10)? as u32,
+                        x_offset: read_u16(ch, 12)? as i16 as i32,
+                        y_offset: read_u16(ch, 14)? as i16 as i32,
+                        x_advance: read_u16(ch, 16)? as i16 as i32,
+                        page: ch[18] as u32,
+                    });
+                }
+            }
+            _ => {}
+        }
+        offset += 5 + size;
+    }
+
+    Ok(font)
+}
+
+/// Read a BMFont descriptor in any of its three variants. The variant is recognized
+/// from the start of the file.
+pub fn parse_fnt(bytes: &[u8]) -> Result<FntFont, String> {
+    if bytes.starts_with(b"BMF") {
+        return parse_binary(bytes);
+    }
+    let contents = String::from_utf8_lossy(bytes);
+    let contents = contents.trim_start_matches('\u{feff}').trim_start();
+    if contents.starts_with('<') {
+        fnt_from_tags(&xml_tags(contents))
+    } else {
+        fnt_from_tags(&text_tags(contents))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_font() -> FntFont {
+        FntFont {
+            face: String::from("Free Mono"),
+            size: 61,
+            padding: 3,
+            line_height: 64,
+            base: 50,
+            scale_width: 1024,
+            scale_height: 512,
+            pages: vec![String::from("font_0.png"), String::from("font_1.png")],
+            chars: vec![
+                FntChar { id: 32, x: 0, y: 0, width: 61, height: 61, x_offset: 0, y_offset: -3, x_advance: 37, page: 0 },
+                FntChar { id: 103, x: 64, y: 128, width: 61, height: 61, x_offset: -2, y_offset: 11, x_advance: 37, page: 1 },
+                FntChar { id: 0x416, x: 960, y: 448, width: 61, height: 61, x_offset: 1, y_offset: 0, x_advance: 37, page: 0 },
+            ],
+        }
+    }
+
+    fn round_trip(format: ExportFormat) -> FntFont {
+        let mut bytes = Vec::new();
+        write_fnt(&sample_font(), format, &mut bytes).unwrap();
+
+        parse_fnt(&bytes).unwrap()
+    }
+
+    #[test]
+    fn text_descriptor_round_trips() {
+        assert_eq!(round_trip(ExportFormat::FntText), sample_font());
+    }
+
+    #[test]
+    fn xml_descriptor_round_trips() {
+        assert_eq!(round_trip(ExportFormat::FntXml), sample_font());
+    }
+
+    #[test]
+    fn binary_descriptor_round_trips() {
+        assert_eq!(round_trip(ExportFormat::FntBinary), sample_font());
+    }
+
+    #[test]
+    fn parse_hand_written_xml_descriptor() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- Written by hand. -->
+<font>
+  <info face="Tom &amp; Jerry" size="-32" bold="0" italic="0" padding="1,2,1,2" spacing="1,1"/>
+  <common lineHeight="36" base="29" scaleW="256" scaleH="128" pages="2" packed="0"/>
+  <pages>
+    <page id="1" file="tom_1.png" />
+    <page id="0" file="tom_0.png" />
+  </pages>
+  <chars count="2">
+    <char id="65" x="10" y="20" width="18" height="24" xoffset="-1" yoffset="5" xadvance="17" page="1" chnl="15" />
+    <char id="66" x="30" y="20" width="16" height="24" xoffset="1" yoffset="5" xadvance="18" page="0" chnl="15" />
+  </chars>
+</font>
+"#;
+        let font = parse_fnt(xml.as_bytes()).unwrap();
+
+        assert_eq!(font.face, "Tom & Jerry");
+        assert_eq!(font.size, 32);
+        assert_eq!(font.padding, 2);
+        assert_eq!((font.line_height, font.base), (36, 29));
+        assert_eq!((font.scale_width, font.scale_height), (256, 128));
+        assert_eq!(font.pages, vec![String::from("tom_0.png"), String::from("tom_1.png")]);
+        assert_eq!(font.chars, vec![
+            FntChar { id: 65, x: 10, y: 20, width: 18, height: 24, x_offset: -1, y_offset: 5, x_advance: 17, page: 1 },
+            FntChar { id: 66, x: 30, y: 20, width: 16, height: 24, x_offset: 1, y_offset: 5, x_advance: 18, page: 0 },
+        ]);
+    }
+
+    #[test]
+    fn parse_descriptor_with_a_missing_page() {
+        let text = "info face=\"Gap\" size=16\npage id=1 file=\"gap_1.png\"\n";
+
+        assert_eq!(parse_fnt(text.as_bytes()), Err(String::from("The page 0 is missing.")));
+    }
+
+    #[test]
+    fn parse_descriptor_with_a_page_id_out_of_range() {
+        let text = "common lineHeight=16 base=12 scaleW=64 scaleH=64 pages=2\npage id=2 file=\"big_2.png\"\n";
+        assert_eq!(
+            parse_fnt(text.as_bytes()),
+            Err(String::from("The page id 2 is not below the page count of 2."))
+        );
+
+        // Without a page count, ids are still held to the most pages a descriptor can have.
+        let text = "page id=4000000000 file=\"huge.png\"\n";
+        assert_eq!(
+            parse_fnt(text.as_bytes()),
+            Err(String::from("The page id 4000000000 is not below the page count of 256."))
+        );
+    }
+}
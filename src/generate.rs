@@ -0,0 +1,428 @@
+use crate::charset::CharacterSet;
+
+use rusttype::{point, Font, Scale};
+use serde_json::json;
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str;
+
+
+#[derive(Clone, Debug)]
+pub struct ParseAtlasOriginError {
+    input: String,
+}
+
+impl fmt::Display for ParseAtlasOriginError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid atlas origin `{}`. Expected one of: bottom-left, top-left.", self.input)
+    }
+}
+
+impl error::Error for ParseAtlasOriginError {}
+
+/// The corner of the atlas image that texture coordinates are measured from, as
+/// recorded in the atlas metadata. A `TopLeft` atlas stores its image bottom row
+/// first, so once it is read and uploaded, its top row lands at the origin.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AtlasOrigin {
+    BottomLeft,
+    TopLeft,
+}
+
+impl AtlasOrigin {
+    /// The name of the origin in the metadata of a `.bmfa` file.
+    fn metadata_name(self) -> &'static str {
+        match self {
+            AtlasOrigin::BottomLeft => "BottomLeft",
+            AtlasOrigin::TopLeft => "TopLeft",
+        }
+    }
+}
+
+impl fmt::Display for AtlasOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AtlasOrigin::BottomLeft => write!(f, "bottom-left"),
+            AtlasOrigin::TopLeft => write!(f, "top-left"),
+        }
+    }
+}
+
+impl str::FromStr for AtlasOrigin {
+    type Err = ParseAtlasOriginError;
+
+    fn from_str(st: &str) -> Result<AtlasOrigin, ParseAtlasOriginError> {
+        match st {
+            "bottom-left" => Ok(AtlasOrigin::BottomLeft),
+            "top-left" => Ok(AtlasOrigin::TopLeft),
+            _ => Err(ParseAtlasOriginError { input: st.to_string() }),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParseCodePointsError {
+    input: String,
+}
+
+impl fmt::Display for ParseCodePointsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "Invalid code points `{}`. Expected a comma separated list of code points such as 65 or U+0041, \
+            ranges such as 32-126, or character sets such as latin-1.",
+            self.input
+        )
+    }
+}
+
+impl error::Error for ParseCodePointsError {}
+
+/// The code points to put in a generated atlas, in code point order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodePoints(pub Vec<usize>);
+
+/// Parse a single code point, written in decimal, or in hexadecimal with a `U+`
+/// or `0x` prefix.
+fn parse_code_point(st: &str) -> Option<usize> {
+    let st = st.trim();
+    let code_point = if st.starts_with("U+") || st.starts_with("u+") || st.starts_with("0x") {
+        usize::from_str_radix(&st[2..], 16).ok()?
+    } else {
+        st.parse().ok()?
+    };
+
+    std::char::from_u32(code_point as u32).map(|_| code_point)
+}
+
+impl str::FromStr for CodePoints {
+    type Err = ParseCodePointsError;
+
+    fn from_str(st: &str) -> Result<CodePoints, ParseCodePointsError> {
+        let error = || ParseCodePointsError { input: st.to_string() };
+        let mut code_points = Vec::new();
+        for part in st.split(',').map(|part| part.trim()) {
+            if let Ok(set) = part.parse::<CharacterSet>() {
+                code_points.extend(set.code_points());
+                continue;
+            }
+            let mut bounds = part.splitn(2, '-');
+            let first = parse_code_point(bounds.next().unwrap_or("")).ok_or_else(error)?;
+            let last = match bounds.next() {
+                Some(last) => parse_code_point(last).ok_or_else(error)?,
+                None => first,
+            };
+            if last < first {
+                return Err(error());
+            }
+            // Surrogates are not characters, so a range across them skips them.
+            code_points.extend((first..=last).filter(|&code_point| std::char::from_u32(code_point as u32).is_some()));
+        }
+        code_points.sort();
+        code_points.dedup();
+
+        Ok(CodePoints(code_points))
+    }
+}
+
+#[derive(Debug)]
+pub enum GenerateError {
+    CouldNotReadFont(PathBuf, io::Error),
+    InvalidFont(PathBuf, rusttype::Error),
+    /// The slot is too small to hold any glyph in front of its padding.
+    SlotTooSmall(usize, usize),
+    NoGlyphs(PathBuf),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GenerateError::CouldNotReadFont(ref path, ref e) => {
+                write!(f, "Could not read the font {}. Got error: {}", path.display(), e)
+            }
+            GenerateError::InvalidFont(ref path, ref e) => {
+                write!(f, "Could not read the font {}. Got error: {}", path.display(), e)
+            }
+            GenerateError::SlotTooSmall(slot_glyph_size, padding) => {
+                write!(f, "A slot of {} pixels has no room for glyphs beside a padding of {} pixels.", slot_glyph_size, padding)
+            }
+            GenerateError::NoGlyphs(ref path) => {
+                write!(f, "The font {} has none of the requested glyphs.", path.display())
+            }
+        }
+    }
+}
+
+impl error::Error for GenerateError {}
+
+/// How to lay out a generated atlas.
+#[derive(Clone, Debug)]
+pub struct GenerateOptions {
+    /// The width and height of each glyph's cell in pixels, padding included.
+    pub slot_glyph_size: usize,
+    /// The width in pixels of the empty band along the right and bottom edges of each
+    /// cell that separates its glyph from the next cells.
+    pub padding: usize,
+    pub code_points: Vec<usize>,
+    pub origin: AtlasOrigin,
+}
+
+/// A font atlas rasterized from a TrueType or OpenType font.
+pub struct GeneratedAtlas {
+    pub origin: AtlasOrigin,
+    pub columns: usize,
+    pub rows: usize,
+    pub padding: usize,
+    pub slot_glyph_size: usize,
+    pub glyph_size: usize,
+    pub glyphs: Vec<bmfa::GlyphMetadata>,
+    /// The atlas image as it is stored in a `.bmfa` file: top row first, or bottom row
+    /// first for a `TopLeft` origin.
+    pub image: image::RgbaImage,
+    /// The requested code points the font has no glyph for.
+    pub missing: Vec<usize>,
+}
+
+impl GeneratedAtlas {
+    /// The atlas metadata, in the layout of the `metadata.json` file in a `.bmfa` file.
+    pub fn metadata(&self) -> serde_json::Value {
+        let mut glyph_metadata = serde_json::Map::new();
+        for glyph in self.glyphs.iter() {
+            glyph_metadata.insert(glyph.code_point.to_string(), json!({
+                "code_point": glyph.code_point,
+                "row": glyph.row,
+                "column": glyph.column,
+                "x_min": glyph.x_min,
+                "width": glyph.width,
+                "height": glyph.height,
+                "y_min": glyph.y_min,
+                "y_offset": glyph.y_offset,
+            }));
+        }
+
+        json!({
+            "origin": self.origin.metadata_name(),
+            "width": self.image.width(),
+            "height": self.image.height(),
+            "columns": self.columns,
+            "rows": self.rows,
+            "padding": self.padding,
+            "slot_glyph_size": self.slot_glyph_size,
+            "glyph_size": self.glyph_size,
+            "glyph_metadata": glyph_metadata,
+        })
+    }
+}
+
+/// Rasterize the glyphs of a TrueType or OpenType font into a grid atlas, one glyph
+/// per cell in code point order. As in the atlases the viewer ships with, the glyph size
+/// is the slot size less the padding: each glyph's ink starts at the top left corner of
+/// its cell, and the padding is the band along the right and bottom edges. The font is
+/// scaled so the distance from its ascent to its descent is the glyph size, each glyph's
+/// baseline is kept through its `y_offset`, and the rare glyph that reaches further is
+/// clipped at the padding. A `TopLeft` atlas has its image and each glyph's `y_min`
+/// flipped top to bottom, so `y_min` still points at the glyph's cell in the stored image.
+pub fn generate(font_path: &Path, options: &GenerateOptions) -> Result<GeneratedAtlas, GenerateError> {
+    let bytes = fs::read(font_path).map_err(|e| GenerateError::CouldNotReadFont(font_path.to_path_buf(), e))?;
+    let font = Font::from_bytes(bytes).map_err(|e| GenerateError::InvalidFont(font_path.to_path_buf(), e))?;
+    let slot = options.slot_glyph_size;
+    let padding = options.padding;
+    if slot <= padding {
+        return Err(GenerateError::SlotTooSmall(slot, padding));
+    }
+    let glyph_size = slot - padding;
+    let scale = Scale::uniform(glyph_size as f32);
+    let ascent = font.v_metrics(scale).ascent;
+
+    // Glyph zero is the font's placeholder for characters it does not have.
+    let (present, missing): (Vec<usize>, Vec<usize>) = options.code_points.iter()
+        .cloned()
+        .partition(|&code_point| {
+            std::char::from_u32(code_point as u32).map_or(false, |ch| font.glyph(ch).id().0 != 0)
+        });
+    if present.is_empty() {
+        return Err(GenerateError::NoGlyphs(font_path.to_path_buf()));
+    }
+
+    let columns = usize::max((present.len() as f32).sqrt().ceil() as usize, 1);
+    let rows = (present.len() + columns - 1) / columns;
+    let (width, height) = (columns * slot, rows * slot);
+    let mut image = image::RgbaImage::new(width as u32, height as u32);
+    let mut glyphs = Vec::with_capacity(present.len());
+    for (index, &code_point) in present.iter().enumerate() {
+        let (row, column) = (index / columns, index % columns);
+        let ch = std::char::from_u32(code_point as u32).unwrap();
+        let scaled = font.glyph(ch).scaled(scale);
+        let advance = scaled.h_metrics().advance_width;
+        // Place the pen at the top of the line, so the bounding box is measured from there.
+        let positioned = scaled.positioned(point(0.0, ascent));
+
+        let (ink_height, y_offset) = match positioned.pixel_bounding_box() {
+            Some(bounding_box) => {
+                // Glyphs that reach left of the pen are shifted right to stay in the cell.
+                let left = column * slot + (i32::max(bounding_box.min.x, 0) as usize);
+                let top = row * slot;
+                let (right, bottom) = ((column + 1) * slot - padding, (row + 1) * slot - padding);
+                positioned.draw(|x, y, coverage| {
+                    let (x, y) = (left + x as usize, top + y as usize);
+                    if x < right && y < bottom {
+                        let alpha = (255.0 * coverage).round() as u8;
+                        image.put_pixel(x as u32, y as u32, image::Rgba([255, 255, 255, alpha]));
+                    }
+                });
+                (bounding_box.height(), bounding_box.min.y)
+            }
+            None => (0, 0),
+        };
+
+        let cell_top = match options.origin {
+            AtlasOrigin::BottomLeft => row * slot,
+            AtlasOrigin::TopLeft => (rows - 1 - row) * slot,
+        };
+        glyphs.push(bmfa::GlyphMetadata {
+            code_point: code_point,
+            row: row,
+            column: column,
+            x_min: ((column * slot) as f32) / (width as f32),
+            y_min: (cell_top as f32) / (height as f32),
+            width: advance / (slot as f32),
+            height: (usize::min(ink_height as usize, glyph_size) as f32) / (slot as f32),
+            y_offset: (y_offset as f32) / (slot as f32),
+        });
+    }
+
+    if options.origin == AtlasOrigin::TopLeft {
+        image = image::imageops::flip_vertical(&image);
+    }
+
+    Ok(GeneratedAtlas {
+        origin: options.origin,
+        columns: columns,
+        rows: rows,
+        padding: padding,
+        slot_glyph_size: slot,
+        glyph_size: glyph_size,
+        glyphs: glyphs,
+        image: image,
+        missing: missing,
+    })
+}
+
+/// Write a generated atlas as a `.bmfa` file: an uncompressed zip archive holding
+/// the metadata as `metadata.json` and the image as `atlas.png`.
+pub fn write_bmfa(atlas: &GeneratedAtlas, path: &Path) -> io::Result<()> {
+    let mut png = Vec::new();
+    image::png::PNGEncoder::new(&mut png)
+        .encode(&atlas.image, atlas.image.width(), atlas.image.height(), image::RGBA(8))?;
+
+    let file = fs::File::create(path)?;
+    let mut archive = zip::ZipWriter::new(io::BufWriter::new(file));
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    archive.start_file("metadata.json", options)?;
+    serde_json::to_writer_pretty(&mut archive, &atlas.metadata())?;
+    archive.start_file("atlas.png", options)?;
+    archive.write_all(&png)?;
+    archive.finish()?.flush()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+
+    /// A tiny font with box shaped glyphs for the space, `A`, which sits on the baseline,
+    /// and `B`, which hangs below it. It has no glyph for `C`.
+    fn boxes_font() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join("boxes.ttf")
+    }
+
+    #[test]
+    fn parse_code_points() {
+        let code_points: CodePoints = "U+0041, 0x20,48-50".parse().unwrap();
+
+        assert_eq!(code_points, CodePoints(vec![32, 48, 49, 50, 65]));
+        assert!("50-48".parse::<CodePoints>().is_err());
+        assert!("U+D800".parse::<CodePoints>().is_err());
+    }
+
+    /// Generate an atlas of the space, `A` and `B` from the box font, write it and read
+    /// it back.
+    fn generate_and_load(origin: AtlasOrigin) -> bmfa::BitmapFontAtlas {
+        let options = GenerateOptions {
+            slot_glyph_size: 32,
+            padding: 2,
+            code_points: vec![32, 65, 66, 67],
+            origin: origin,
+        };
+        let generated = generate(&boxes_font(), &options).unwrap();
+        assert_eq!(generated.missing, vec![67]);
+
+        let path = std::env::temp_dir().join(format!("fontview_generate_{}_{}.bmfa", origin, std::process::id()));
+        write_bmfa(&generated, &path).unwrap();
+        let atlas = bmfa::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        atlas.unwrap()
+    }
+
+    /// Check that each glyph's cell, found from its `x_min` and `y_min` in the stored
+    /// image, holds ink for the glyphs with any, and that no ink lies outside the cells.
+    fn assert_glyphs_land_on_ink(atlas: &bmfa::BitmapFontAtlas) {
+        let slot = atlas.slot_glyph_size;
+        // The atlas image is read bottom row first, while `y_min` counts from the top.
+        let alpha = |x: usize, y: usize| atlas.image[4 * ((atlas.height - 1 - y) * atlas.width + x) + 3];
+        let ink = |left: usize, top: usize, size_x: usize, size_y: usize| -> usize {
+            (top..top + size_y)
+                .flat_map(|y| (left..left + size_x).map(move |x| (x, y)))
+                .filter(|&(x, y)| alpha(x, y) != 0)
+                .count()
+        };
+
+        let mut glyph_ink = 0;
+        for &code_point in [32, 65, 66].iter() {
+            let metadata = &atlas.glyph_metadata[&code_point];
+            let left = (metadata.x_min * (atlas.width as f32)).round() as usize;
+            let top = (metadata.y_min * (atlas.height as f32)).round() as usize;
+            let cell_ink = ink(left, top, slot, slot);
+            assert_eq!(cell_ink > 0, code_point != 32, "U+{:04X} has {} inked pixels", code_point, cell_ink);
+            glyph_ink += cell_ink;
+        }
+        assert_eq!(glyph_ink, ink(0, 0, atlas.width, atlas.height));
+    }
+
+    #[test]
+    fn generated_atlas_loads() {
+        let atlas = generate_and_load(AtlasOrigin::BottomLeft);
+
+        assert_eq!(format!("{:?}", atlas.origin), "BottomLeft");
+        assert_eq!((atlas.slot_glyph_size, atlas.padding, atlas.glyph_size), (32, 2, 30));
+        assert_eq!((atlas.columns, atlas.rows), (2, 2));
+        let mut code_points: Vec<usize> = atlas.glyph_metadata.keys().cloned().collect();
+        code_points.sort();
+        assert_eq!(code_points, vec![32, 65, 66]);
+        // The top of `B` is lower than the top of `A`, so its cell is placed further down the line.
+        assert!(atlas.glyph_metadata[&66].y_offset > atlas.glyph_metadata[&65].y_offset);
+        assert_glyphs_land_on_ink(&atlas);
+
+        let options = validate::ValidationOptions { required: vec![], allow_shared_cells: false };
+        assert_eq!(validate::validate(&atlas, &options), vec![]);
+    }
+
+    #[test]
+    fn generated_top_left_atlas_loads() {
+        let atlas = generate_and_load(AtlasOrigin::TopLeft);
+
+        assert_eq!(format!("{:?}", atlas.origin), "TopLeft");
+        assert_eq!((atlas.columns, atlas.rows), (2, 2));
+        // `A` is in the top row of the grid and `B` in the bottom one, so flipping the image
+        // swaps the rows their cells are stored in.
+        assert_eq!((atlas.glyph_metadata[&65].row, atlas.glyph_metadata[&65].y_min), (0, 0.5));
+        assert_eq!((atlas.glyph_metadata[&66].row, atlas.glyph_metadata[&66].y_min), (1, 0.0));
+        assert_glyphs_land_on_ink(&atlas);
+    }
+}
@@ -7,8 +7,7 @@ use glfw;
 use glfw::{Context, Glfw};
 
 use std::ffi::{CStr, CString};
-use std::fs::File;
-use std::io::{Read, BufReader};
+use std::io::Read;
 use std::sync::mpsc::Receiver;
 use std::ptr;
 use std::fmt;
@@ -21,6 +20,9 @@ use log::{info, error};
 // 256 Kilobytes.
 const MAX_SHADER_LENGTH: usize = 262144;
 
+/// The title of the viewer window.
+pub const WINDOW_TITLE: &str = "Bitmapped Font Viewer";
+
 
 #[inline]
 pub fn glubyte_ptr_to_string(cstr: *const GLubyte) -> String {
@@ -150,8 +152,6 @@ pub struct GLState {
     pub height: u32,
     pub channel_depth: u32,
     pub running_time_seconds: f64,
-    pub framerate_time_seconds: f64,
-    pub frame_count: u32,
 }
 
 #[cfg(target_os = "macos")]
@@ -202,11 +202,14 @@ pub fn start_gl(width: u32, height: u32) -> Result<GLState, String> {
     info!("Using GLFW version {}", glfw::get_version_string());
 
     // Start a GL context and OS window using the GLFW helper library.
-    let glfw = __init_glfw();
+    let mut glfw = __init_glfw();
+    // Request an sRGB capable default framebuffer so gamma correct rendering can be
+    // switched on with `GL_FRAMEBUFFER_SRGB`.
+    glfw.window_hint(glfw::WindowHint::SRgbCapable(true));
 
     info!("Started GLFW successfully");
     let maybe_glfw_window = glfw.create_window(
-        width, height, &format!("Bitmapped Font Viewer"), glfw::WindowMode::Windowed
+        width, height, WINDOW_TITLE, glfw::WindowMode::Windowed
     );
     let (mut window, events) = match maybe_glfw_window {
         Some(tuple) => tuple,
@@ -218,9 +221,14 @@ pub fn start_gl(width: u32, height: u32) -> Result<GLState, String> {
 
     window.make_current();
     window.set_key_polling(true);
+    window.set_char_polling(true);
     window.set_size_polling(true);
+    window.set_framebuffer_size_polling(true);
     window.set_refresh_polling(true);
-    window.set_size_polling(true);
+    window.set_scroll_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_mouse_button_polling(true);
+    window.set_cursor_enter_polling(true);
 
     // Load the OpenGl function pointers.
     gl::load_with(|symbol| { window.get_proc_address(symbol) as *const _ });
@@ -243,8 +251,6 @@ pub fn start_gl(width: u32, height: u32) -> Result<GLState, String> {
         height: height,
         channel_depth: 3,
         running_time_seconds: 0.0,
-        framerate_time_seconds: 0.0,
-        frame_count: 0,
     })
 }
 
@@ -259,27 +265,12 @@ pub fn update_timers(context: &mut GLState) -> f64 {
     elapsed_seconds
 }
 
-/// Update the framerate and display in the window titlebar.
-#[inline]
-pub fn update_fps_counter(context: &mut GLState) {     
-    let current_time_seconds = context.glfw.get_time();
-    let elapsed_seconds = current_time_seconds - context.framerate_time_seconds;
-    if elapsed_seconds > 0.5 {
-        context.framerate_time_seconds = current_time_seconds;
-        let fps = context.frame_count as f64 / elapsed_seconds;
-        context.window.set_title(&format!("Googly Blocks @ {:.2} FPS", fps));
-        context.frame_count = 0;
-    }
-
-    context.frame_count += 1;
-}
-
 #[derive(Clone, Debug)]
 pub enum ShaderCompilationError {
     ShaderNotFound(String),
     CouldNotParseShader(String),
-    CouldNotCompileShader(String),
-    CouldNotLinkShader,
+    CouldNotCompileShader(String, ShaderLog),
+    CouldNotLinkShader(ProgramLog),
     ShaderValidationFailed,
 }
 
@@ -292,11 +283,11 @@ impl fmt::Display for ShaderCompilationError {
             &ShaderCompilationError::CouldNotParseShader(ref file_name) => {
                 write!(f, "The shader file exists, but there was an error in reading it: {}", file_name.to_string())
             }
-            &ShaderCompilationError::CouldNotCompileShader(ref file_name) => {
-                write!(f, "The shader could not be compiled: {}", file_name.to_string())
+            &ShaderCompilationError::CouldNotCompileShader(ref file_name, ref log) => {
+                write!(f, "The shader could not be compiled: {}\n{}", file_name.to_string(), log)
             }
-            &ShaderCompilationError::CouldNotLinkShader => {
-                write!(f, "The shader program could not be linked.")
+            &ShaderCompilationError::CouldNotLinkShader(ref log) => {
+                write!(f, "The shader program could not be linked.\n{}", log)
             }
             &ShaderCompilationError::ShaderValidationFailed => {
                 write!(f, "Shader validation failed.")
@@ -326,6 +317,7 @@ pub fn parse_shader<P: AsRef<Path>, R: Read>(
 
 /// A record containing all the relevant compilation log information for a
 /// given GLSL shader compiled at run time.
+#[derive(Clone, Debug)]
 pub struct ShaderLog {
     index: GLuint,
     log: String,
@@ -399,8 +391,11 @@ pub fn create_shader<P: AsRef<Path>, R: Read>(
     if params != gl::TRUE as i32 {
         let log = shader_info_log(shader);
         error!("ERROR: GL shader index {} did not compile\n{}", shader, log);
+        unsafe {
+            gl::DeleteShader(shader);
+        }
         return Err(
-            ShaderCompilationError::CouldNotCompileShader(format!("{}", disp))
+            ShaderCompilationError::CouldNotCompileShader(format!("{}", disp), log)
         );
     }
     info!("Shader compiled with index {}.\n", shader);
@@ -411,6 +406,7 @@ pub fn create_shader<P: AsRef<Path>, R: Read>(
 
 /// A record containing all the relevant compilation log information for a
 /// given GLSL shader program compiled at run time.
+#[derive(Clone, Debug)]
 pub struct ProgramLog {
     index: GLuint,
     log: String,
@@ -488,9 +484,15 @@ pub fn create_program(
         gl::GetProgramiv(program, gl::LINK_STATUS, &mut params);
     }
     if params != gl::TRUE as i32 {
+        let log = program_info_log(program);
         error!("ERROR: could not link shader programme GL index {}\n", program);
-        error!("{}", program_info_log(program));
-        return Err(ShaderCompilationError::CouldNotLinkShader);
+        error!("{}", log);
+        unsafe {
+            gl::DeleteProgram(program);
+            gl::DeleteShader(vertex_shader);
+            gl::DeleteShader(fragment_shader);
+        }
+        return Err(ShaderCompilationError::CouldNotLinkShader(log));
     }
 
     unsafe {
@@ -502,37 +504,6 @@ pub fn create_program(
     Ok(program)
 }
 
-/// Compile and link a shader program directly from the files.
-pub fn create_program_from_files<P: AsRef<Path>, Q: AsRef<Path>>(
-    context: &GLState,
-    vert_file_name: P, frag_file_name: Q) -> Result<GLuint, ShaderCompilationError> {
-
-    let mut vert_reader = BufReader::new(match File::open(&vert_file_name) {
-        Ok(val) => val,
-        Err(_) => {
-            let disp = vert_file_name.as_ref().display().to_string();
-            return Err(ShaderCompilationError::ShaderNotFound(disp));
-        }
-    });
-    let mut frag_reader = BufReader::new(match File::open(&frag_file_name) {
-        Ok(val) => val,
-        Err(_) => {
-            let disp = frag_file_name.as_ref().display().to_string();
-            return Err(ShaderCompilationError::ShaderNotFound(disp));
-        }
-    });
-
-    let vertex_shader = create_shader(
-        context, &mut vert_reader, vert_file_name, gl::VERTEX_SHADER
-    )?;
-    let fragment_shader = create_shader(
-        context, &mut frag_reader, frag_file_name, gl::FRAGMENT_SHADER
-    )?;
-    let program = create_program(context, vertex_shader, fragment_shader)?;
-
-    Ok(program)
-}
-
 /// Compile and link a shader program directly from any readable sources.
 pub fn create_program_from_reader<R1: Read, P1: AsRef<Path>, R2: Read, P2: AsRef<Path>>(
     context: &GLState,
@@ -542,9 +513,15 @@ pub fn create_program_from_reader<R1: Read, P1: AsRef<Path>, R2: Read, P2: AsRef
     let vertex_shader = create_shader(
         context, vert_reader, vert_file_name, gl::VERTEX_SHADER
     )?;
-    let fragment_shader = create_shader(
-        context, frag_reader, frag_file_name, gl::FRAGMENT_SHADER
-    )?;
+    let fragment_shader = match create_shader(context, frag_reader, frag_file_name, gl::FRAGMENT_SHADER) {
+        Ok(val) => val,
+        Err(e) => {
+            unsafe {
+                gl::DeleteShader(vertex_shader);
+            }
+            return Err(e);
+        }
+    };
     let program = create_program(context, vertex_shader, fragment_shader)?;
 
     Ok(program)
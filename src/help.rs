@@ -0,0 +1,39 @@
+use crate::color::Color;
+use crate::panel;
+use crate::panel::{Anchor, Panel};
+
+
+/// The size of the text in the help panel.
+const HELP_SCALE_PX: f32 = 32.0;
+
+/// The key bindings listed in the help panel.
+const HELP_TEXT: &str = "\
+H          show or hide this help
+Esc        quit, or stop editing
+F2         edit the text
+F12        save a screenshot
+Tab        text or atlas inspector
+= -        grow or shrink the text
+C B        cycle text or background color
+S O G      shadow, outline or glow
+[ ]        outline width
+Arrows     move the shadow
+, . ; '    distance field edge
+F N M      filters or mipmaps
+A W        anisotropy or wrap mode
+D          all debug overlays
+1 - 5      one debug overlay
+I          glyph inspector
+P          performance statistics
+Scroll     zoom, drag to pan
+Home       reset the view";
+
+
+/// Build the help panel in the top left corner of a window `width` by `height` pixels
+/// in size. The key bindings are followed by the current settings they change, given
+/// in `status`, one per line.
+pub fn help_panel(atlas: &bmfa::BitmapFontAtlas, status: &str, width: u32, height: u32, background: Color) -> Panel {
+    let text = format!("{}\n\n{}", HELP_TEXT, status);
+
+    panel::panel(atlas, &text, HELP_SCALE_PX, Anchor::TopLeft, width, height, background)
+}
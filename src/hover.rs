@@ -0,0 +1,49 @@
+use crate::color::Color;
+use crate::layout::PlacedGlyph;
+use crate::panel;
+use crate::panel::{Anchor, Panel};
+
+use std::fmt::Write;
+
+
+/// The size of the text in the glyph inspector panel.
+const PANEL_SCALE_PX: f32 = 40.0;
+
+
+/// Describe a glyph's atlas metadata, one field per line.
+pub fn glyph_description(atlas: &bmfa::BitmapFontAtlas, code_point: usize) -> String {
+    let mut description = String::new();
+    let ch = std::char::from_u32(code_point as u32).unwrap_or(std::char::REPLACEMENT_CHARACTER);
+    writeln!(description, "U+{:04X} '{}'", code_point, ch).unwrap();
+    match atlas.glyph_metadata.get(&code_point) {
+        Some(metadata) => {
+            writeln!(description, "row {} column {}", metadata.row, metadata.column).unwrap();
+            writeln!(description, "x_min {} y_min {}", metadata.x_min, metadata.y_min).unwrap();
+            writeln!(description, "width {} height {}", metadata.width, metadata.height).unwrap();
+            write!(description, "y_offset {}", metadata.y_offset).unwrap();
+        }
+        None => {
+            write!(description, "not in atlas").unwrap();
+        }
+    }
+
+    description
+}
+
+/// Build the glyph inspector panel for a hovered glyph, along with a highlight around
+/// the glyph. The panel sits just below and to the right of the cursor, and moves to stay
+/// inside the window. The hovered glyph's box is given in screen coordinates, after any
+/// view transform.
+pub fn hover_panel(
+    atlas: &bmfa::BitmapFontAtlas, glyph: &PlacedGlyph, glyph_box: (f32, f32, f32, f32),
+    cursor: (f32, f32), width: u32, height: u32,
+    background: Color, highlight: Color) -> Panel {
+
+    let description = glyph_description(atlas, glyph.code_point);
+    let anchor = Anchor::Cursor(cursor.0, cursor.1);
+    let mut hover = panel::panel(atlas, &description, PANEL_SCALE_PX, anchor, width, height, background);
+    let (left, top, right, bottom) = glyph_box;
+    hover.overlay.push_rect(left, top, right, bottom, highlight);
+
+    hover
+}
@@ -0,0 +1,503 @@
+use crate::fnt;
+use crate::fnt::FntFont;
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+
+/// The padding in pixels between the glyphs in an imported atlas.
+const IMPORT_PADDING: usize = 1;
+
+
+#[derive(Debug)]
+pub enum ImportError {
+    CouldNotReadFile(PathBuf, io::Error),
+    CouldNotReadImage(PathBuf, image::ImageError),
+    InvalidFont(PathBuf, String),
+    /// A PNG grid was given without the size of its cells.
+    NoCellSize(PathBuf),
+    NoGlyphs(PathBuf),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImportError::CouldNotReadFile(ref path, ref e) => {
+                write!(f, "Could not read {}. Got error: {}", path.display(), e)
+            }
+            ImportError::CouldNotReadImage(ref path, ref e) => {
+                write!(f, "Could not read the image {}. Got error: {}", path.display(), e)
+            }
+            ImportError::InvalidFont(ref path, ref message) => {
+                write!(f, "Could not read the font {}. {}", path.display(), message)
+            }
+            ImportError::NoCellSize(ref path) => {
+                write!(f, "The image {} is read as a grid of glyphs, which needs --cell-size.", path.display())
+            }
+            ImportError::NoGlyphs(ref path) => {
+                write!(f, "The font {} has no glyphs.", path.display())
+            }
+        }
+    }
+}
+
+impl error::Error for ImportError {}
+
+/// How to read fonts that do not describe themselves fully.
+#[derive(Copy, Clone, Debug)]
+pub struct ImportOptions {
+    /// The width and height of the cells in a PNG grid font.
+    pub cell_size: Option<(u32, u32)>,
+    /// The code point of the first cell in a PNG grid font.
+    pub first_code_point: usize,
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            cell_size: None,
+            first_code_point: 32,
+        }
+    }
+}
+
+/// Parse a cell size given as `WIDTHxHEIGHT`, or as a single number for square cells.
+pub fn parse_cell_size(st: &str) -> Result<(u32, u32), String> {
+    let parse = |value: &str| -> Result<u32, String> {
+        match value.trim().parse() {
+            Ok(size) if size > 0 => Ok(size),
+            _ => Err(format!("Invalid cell size `{}`. Expected WIDTHxHEIGHT or a single size.", st)),
+        }
+    };
+    let mut parts = st.splitn(2, 'x');
+    let width = parse(parts.next().unwrap_or(""))?;
+    let height = match parts.next() {
+        Some(height) => parse(height)?,
+        None => width,
+    };
+
+    Ok((width, height))
+}
+
+/// A glyph read from another font format, before it is packed into an atlas. The
+/// image has its top row first, and the offsets and advance are in pixels from the
+/// pen position at the top of the line.
+struct ImportedGlyph {
+    code_point: usize,
+    image: image::RgbaImage,
+    x_offset: i32,
+    y_offset: i32,
+    advance: i32,
+}
+
+/// Read an image, making it RGBA. Images without any transparency, such as white
+/// glyphs drawn on black, take their alpha channel from their brightness instead.
+fn load_image(path: &Path) -> Result<image::RgbaImage, ImportError> {
+    let mut image = image::open(path)
+        .map_err(|e| ImportError::CouldNotReadImage(path.to_path_buf(), e))?
+        .to_rgba();
+    if image.pixels().all(|pixel| pixel[3] == 255) {
+        for pixel in image.pixels_mut() {
+            let luminance = 0.2126 * (pixel[0] as f32) + 0.7152 * (pixel[1] as f32) + 0.0722 * (pixel[2] as f32);
+            *pixel = image::Rgba([255, 255, 255, luminance.round() as u8]);
+        }
+    }
+
+    Ok(image)
+}
+
+/// Copy a rectangle out of an image. The parts of the rectangle outside the image
+/// are left transparent.
+fn crop(image: &image::RgbaImage, x: u32, y: u32, width: u32, height: u32) -> image::RgbaImage {
+    let mut cropped = image::RgbaImage::new(width, height);
+    for (cx, cy, pixel) in cropped.enumerate_pixels_mut() {
+        let (image_x, image_y) = ((x as u64) + (cx as u64), (y as u64) + (cy as u64));
+        if image_x < image.width() as u64 && image_y < image.height() as u64 {
+            *pixel = *image.get_pixel(image_x as u32, image_y as u32);
+        }
+    }
+
+    cropped
+}
+
+/// Pack imported glyphs into a square grid atlas. Every slot is large enough for the
+/// largest glyph followed by the padding that separates it from the next slots. Each
+/// glyph sits at the top of its slot, shifted right by its horizontal offset, and the
+/// slot is placed `y_offset` below the top of the line so the glyph lands where the font
+/// placed it. The atlas has no horizontal offsets, so glyphs that reach left of the pen
+/// grow every slot on the left instead, moving the pen of every glyph right by the same
+/// amount.
+fn build_atlas(mut glyphs: Vec<ImportedGlyph>, glyph_size: usize) -> bmfa::BitmapFontAtlas {
+    glyphs.sort_by_key(|glyph| glyph.code_point);
+    let pen_x = glyphs.iter().map(|glyph| -(glyph.x_offset as i64)).max().map_or(0, |pen_x| i64::max(pen_x, 0));
+    let content_size = glyphs.iter()
+        .map(|glyph| {
+            let width = ((pen_x + glyph.x_offset as i64) as u32) + glyph.image.width();
+            u32::max(width, glyph.image.height()) as usize
+        })
+        .max()
+        .unwrap_or(0);
+    let slot = usize::max(content_size, 1) + IMPORT_PADDING;
+    let columns = usize::max((glyphs.len() as f32).sqrt().ceil() as usize, 1);
+    let rows = usize::max((glyphs.len() + columns - 1) / columns, 1);
+    let (width, height) = (columns * slot, rows * slot);
+
+    let mut image = image::RgbaImage::new(width as u32, height as u32);
+    let mut glyph_metadata = HashMap::new();
+    for (index, glyph) in glyphs.iter().enumerate() {
+        let (row, column) = (index / columns, index % columns);
+        let left = column * slot + ((pen_x + glyph.x_offset as i64) as usize);
+        let top = row * slot;
+        for (x, y, pixel) in glyph.image.enumerate_pixels() {
+            image.put_pixel(left as u32 + x, top as u32 + y, *pixel);
+        }
+
+        glyph_metadata.insert(glyph.code_point, bmfa::GlyphMetadata {
+            code_point: glyph.code_point,
+            row: row,
+            column: column,
+            x_min: ((column * slot) as f32) / (width as f32),
+            y_min: ((row * slot) as f32) / (height as f32),
+            width: (glyph.advance as f32) / (slot as f32),
+            height: (glyph.image.height() as f32) / (slot as f32),
+            y_offset: (glyph.y_offset as f32) / (slot as f32),
+        });
+    }
+
+    // The viewer expects the atlas image bottom row first, as it is uploaded to OpenGL.
+    let row_size = 4 * width;
+    let mut flipped = Vec::with_capacity(row_size * height);
+    for image_row in image.into_raw().chunks(row_size).rev() {
+        flipped.extend_from_slice(image_row);
+    }
+
+    bmfa::BitmapFontAtlas {
+        origin: bmfa::Origin::BottomLeft,
+        width: width,
+        height: height,
+        columns: columns,
+        rows: rows,
+        padding: IMPORT_PADDING,
+        slot_glyph_size: slot,
+        glyph_size: if glyph_size > 0 { glyph_size } else { content_size },
+        glyph_metadata: glyph_metadata,
+        image: flipped,
+    }
+}
+
+/// Cut the glyphs of a BMFont out of its page images, which are found next to the
+/// font file.
+fn glyphs_from_fnt(path: &Path, font: &FntFont) -> Result<Vec<ImportedGlyph>, ImportError> {
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let pages = font.pages.iter()
+        .map(|page| load_image(&directory.join(page)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut glyphs = Vec::with_capacity(font.chars.len());
+    for ch in font.chars.iter() {
+        let page = pages.get(ch.page as usize).ok_or_else(|| {
+            ImportError::InvalidFont(path.to_path_buf(), format!("The glyph U+{:04X} is on a missing page.", ch.id))
+        })?;
+        let right = (ch.x as u64) + (ch.width as u64);
+        let bottom = (ch.y as u64) + (ch.height as u64);
+        if right > page.width() as u64 || bottom > page.height() as u64 {
+            return Err(ImportError::InvalidFont(
+                path.to_path_buf(), format!("The glyph U+{:04X} lies outside its page.", ch.id)
+            ));
+        }
+        glyphs.push(ImportedGlyph {
+            code_point: ch.id as usize,
+            image: crop(page, ch.x, ch.y, ch.width, ch.height),
+            x_offset: ch.x_offset,
+            y_offset: ch.y_offset,
+            advance: ch.x_advance,
+        });
+    }
+
+    Ok(glyphs)
+}
+
+/// Read a font in the JSON layout of BMFont used by Godot and Unity font importers:
+/// an object with `pages` file names, `chars` objects with the same fields as BMFont
+/// `char` tags, and optional `info` and `common` objects.
+fn fnt_from_json(value: &serde_json::Value) -> Result<FntFont, String> {
+    // Missing fields are zero, as in the text descriptor.
+    let number = |object: &serde_json::Value, key: &str| -> Result<i64, String> {
+        match object.get(key) {
+            Some(value) => value.as_i64().ok_or_else(|| format!("Invalid value `{}` for `{}`.", value, key)),
+            None => Ok(0),
+        }
+    };
+    let signed = |object: &serde_json::Value, key: &str| -> Result<i32, String> {
+        let value = number(object, key)?;
+        if value < i32::min_value() as i64 || value > i32::max_value() as i64 {
+            return Err(format!("Invalid value `{}` for `{}`.", value, key));
+        }
+
+        Ok(value as i32)
+    };
+    let unsigned = |object: &serde_json::Value, key: &str| -> Result<u32, String> {
+        let value = number(object, key)?;
+        if value < 0 || value > u32::max_value() as i64 {
+            return Err(format!("Invalid value `{}` for `{}`.", value, key));
+        }
+
+        Ok(value as u32)
+    };
+
+    let mut font = FntFont::default();
+    if let Some(info) = value.get("info") {
+        font.face = info.get("face").and_then(|face| face.as_str()).unwrap_or("").to_string();
+        font.size = signed(info, "size")?.abs() as u32;
+    }
+    if let Some(common) = value.get("common") {
+        font.line_height = unsigned(common, "lineHeight")?;
+        font.base = unsigned(common, "base")?;
+        font.scale_width = unsigned(common, "scaleW")?;
+        font.scale_height = unsigned(common, "scaleH")?;
+    }
+    font.pages = value.get("pages")
+        .and_then(|pages| pages.as_array())
+        .ok_or_else(|| String::from("It has no `pages` array."))?
+        .iter()
+        .enumerate()
+        .map(|(id, page)| page.as_str().map(String::from).ok_or_else(|| format!("The page {} is not a file name.", id)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Some exporters write the glyphs as an object keyed by code point instead of an array.
+    let chars: Vec<&serde_json::Value> = match value.get("chars") {
+        Some(&serde_json::Value::Array(ref chars)) => chars.iter().collect(),
+        Some(&serde_json::Value::Object(ref chars)) => chars.values().collect(),
+        _ => return Err(String::from("It has no `chars` array.")),
+    };
+    for ch in chars {
+        font.chars.push(fnt::FntChar {
+            id: unsigned(ch, "id")?,
+            x: unsigned(ch, "x")?,
+            y: unsigned(ch, "y")?,
+            width: unsigned(ch, "width")?,
+            height: unsigned(ch, "height")?,
+            x_offset: signed(ch, "xoffset")?,
+            y_offset: signed(ch, "yoffset")?,
+            x_advance: signed(ch, "xadvance")?,
+            page: unsigned(ch, "page")?,
+        });
+    }
+
+    Ok(font)
+}
+
+/// Cut the cells of a grid of glyphs out of an image. The cells hold consecutive code
+/// points in reading order, starting from `first_code_point`. Every glyph advances by a
+/// whole cell. Empty cells are left out, apart from the space.
+fn glyphs_from_grid(
+    image: &image::RgbaImage, cell_width: u32, cell_height: u32, first_code_point: usize) -> Vec<ImportedGlyph> {
+
+    let columns = image.width() / cell_width;
+    let rows = image.height() / cell_height;
+    let mut glyphs = Vec::new();
+    for index in 0..(columns * rows) {
+        let (row, column) = (index / columns, index % columns);
+        let cell = crop(image, column * cell_width, row * cell_height, cell_width, cell_height);
+        let code_point = first_code_point + index as usize;
+        if code_point != ' ' as usize && cell.pixels().all(|pixel| pixel[3] == 0) {
+            continue;
+        }
+        glyphs.push(ImportedGlyph {
+            code_point: code_point,
+            image: cell,
+            x_offset: 0,
+            y_offset: 0,
+            advance: cell_width as i32,
+        });
+    }
+
+    glyphs
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, ImportError> {
+    fs::read(path).map_err(|e| ImportError::CouldNotReadFile(path.to_path_buf(), e))
+}
+
+/// Load a font atlas. Besides `.bmfa` atlases, AngelCode BMFont `.fnt` files with their
+/// page images, BMFont JSON fonts and PNG grids of glyphs are converted into an atlas,
+/// recognized by their extension.
+pub fn load(path: &Path, options: &ImportOptions) -> Result<bmfa::BitmapFontAtlas, Box<dyn error::Error>> {
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase());
+    let (glyphs, glyph_size) = match extension.as_ref().map(|extension| extension.as_str()) {
+        Some("fnt") => {
+            let font = fnt::parse_fnt(&read_file(path)?)
+                .map_err(|message| ImportError::InvalidFont(path.to_path_buf(), message))?;
+            (glyphs_from_fnt(path, &font)?, font.size as usize)
+        }
+        Some("json") => {
+            let value: serde_json::Value = serde_json::from_slice(&read_file(path)?)
+                .map_err(|e| ImportError::InvalidFont(path.to_path_buf(), e.to_string()))?;
+            let font = fnt_from_json(&value).map_err(|message| ImportError::InvalidFont(path.to_path_buf(), message))?;
+            (glyphs_from_fnt(path, &font)?, font.size as usize)
+        }
+        Some("png") => {
+            let (cell_width, cell_height) = options.cell_size.ok_or_else(|| ImportError::NoCellSize(path.to_path_buf()))?;
+            let image = load_image(path)?;
+            let glyphs = glyphs_from_grid(&image, cell_width, cell_height, options.first_code_point);
+            (glyphs, u32::max(cell_width, cell_height) as usize)
+        }
+        _ => return bmfa::load(path).map_err(|e| Box::new(e) as Box<dyn error::Error>),
+    };
+    if glyphs.is_empty() {
+        return Err(Box::new(ImportError::NoGlyphs(path.to_path_buf())));
+    }
+
+    Ok(build_atlas(glyphs, glyph_size))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A glyph of solid ink `width` by `height` pixels in size.
+    fn solid_glyph(
+        code_point: usize, width: u32, height: u32, x_offset: i32, y_offset: i32, advance: i32) -> ImportedGlyph {
+
+        ImportedGlyph {
+            code_point: code_point,
+            image: image::RgbaImage::from_pixel(width, height, image::Rgba([255, 255, 255, 255])),
+            x_offset: x_offset,
+            y_offset: y_offset,
+            advance: advance,
+        }
+    }
+
+    /// The alpha of a pixel of the atlas image, counting rows from the top.
+    fn alpha(atlas: &bmfa::BitmapFontAtlas, x: usize, y_from_top: usize) -> u8 {
+        atlas.image[4 * ((atlas.height - 1 - y_from_top) * atlas.width + x) + 3]
+    }
+
+    #[test]
+    fn cell_sizes() {
+        assert_eq!(parse_cell_size("16"), Ok((16, 16)));
+        assert_eq!(parse_cell_size("8x12"), Ok((8, 12)));
+        assert_eq!(parse_cell_size(" 8 x 12 "), Ok((8, 12)));
+        for &size in ["", "0", "8x0", "0x8", "x8", "8x", "eight", "8x12x16", "-8"].iter() {
+            assert!(parse_cell_size(size).is_err(), "{} should not be a valid cell size", size);
+        }
+    }
+
+    #[test]
+    fn json_font() {
+        let value = json!({
+            "info": { "face": "Pixel", "size": -16 },
+            "common": { "lineHeight": 18, "base": 14, "scaleW": 64, "scaleH": 32 },
+            "pages": ["pixel_0.png"],
+            "chars": [
+                { "id": 65, "x": 1, "y": 2, "width": 8, "height": 10, "xoffset": -1, "yoffset": 3, "xadvance": 9, "page": 0 },
+                { "id": 66, "x": 10, "width": 7, "height": 10, "xadvance": 8 },
+            ],
+        });
+        let font = fnt_from_json(&value).unwrap();
+
+        assert_eq!(font.face, "Pixel");
+        assert_eq!(font.size, 16);
+        assert_eq!((font.line_height, font.base, font.scale_width, font.scale_height), (18, 14, 64, 32));
+        assert_eq!(font.pages, vec![String::from("pixel_0.png")]);
+        assert_eq!(font.chars, vec![
+            fnt::FntChar { id: 65, x: 1, y: 2, width: 8, height: 10, x_offset: -1, y_offset: 3, x_advance: 9, page: 0 },
+            fnt::FntChar { id: 66, x: 10, y: 0, width: 7, height: 10, x_offset: 0, y_offset: 0, x_advance: 8, page: 0 },
+        ]);
+    }
+
+    #[test]
+    fn json_font_with_chars_keyed_by_code_point() {
+        let value = json!({
+            "pages": ["keyed.png"],
+            "chars": { "65": { "id": 65, "width": 4, "height": 4, "xadvance": 5 } },
+        });
+        let font = fnt_from_json(&value).unwrap();
+
+        assert_eq!(font.chars.len(), 1);
+        assert_eq!(font.chars[0].id, 65);
+    }
+
+    #[test]
+    fn invalid_json_fonts_are_rejected() {
+        let font_with_char = |ch: serde_json::Value| fnt_from_json(&json!({ "pages": ["page.png"], "chars": [ch] }));
+
+        assert_eq!(fnt_from_json(&json!({ "chars": [] })), Err(String::from("It has no `pages` array.")));
+        assert_eq!(fnt_from_json(&json!({ "pages": [] })), Err(String::from("It has no `chars` array.")));
+        assert_eq!(
+            fnt_from_json(&json!({ "pages": [7], "chars": [] })),
+            Err(String::from("The page 0 is not a file name."))
+        );
+        assert_eq!(font_with_char(json!({ "width": -1 })), Err(String::from("Invalid value `-1` for `width`.")));
+        assert_eq!(font_with_char(json!({ "x": 1.5 })), Err(String::from("Invalid value `1.5` for `x`.")));
+        assert_eq!(font_with_char(json!({ "id": "A" })), Err(String::from("Invalid value `\"A\"` for `id`.")));
+        assert_eq!(
+            font_with_char(json!({ "xoffset": 3000000000i64 })),
+            Err(String::from("Invalid value `3000000000` for `xoffset`."))
+        );
+        assert_eq!(
+            font_with_char(json!({ "id": 5000000000i64 })),
+            Err(String::from("Invalid value `5000000000` for `id`."))
+        );
+    }
+
+    #[test]
+    fn grid_cells() {
+        // Three whole cells and part of a fourth, which is left out. The first cell is the
+        // empty space, the second is empty, and the third has a dot in it.
+        let mut image = image::RgbaImage::new(14, 4);
+        image.put_pixel(9, 2, image::Rgba([255, 255, 255, 255]));
+        let glyphs = glyphs_from_grid(&image, 4, 4, 32);
+        let code_points: Vec<usize> = glyphs.iter().map(|glyph| glyph.code_point).collect();
+
+        assert_eq!(code_points, vec![32, 34]);
+        for glyph in glyphs.iter() {
+            assert_eq!(glyph.image.dimensions(), (4, 4));
+            assert_eq!((glyph.x_offset, glyph.y_offset, glyph.advance), (0, 0, 4));
+        }
+        assert_eq!(glyphs[1].image.get_pixel(1, 2)[3], 255);
+        assert_eq!(glyphs[1].image.pixels().filter(|pixel| pixel[3] != 0).count(), 1);
+    }
+
+    #[test]
+    fn atlas_keeps_negative_bearings() {
+        // `A` reaches two pixels left of the pen, so every slot grows by two pixels on the left.
+        let glyphs = vec![
+            solid_glyph(66, 3, 2, 1, 0, 4),
+            solid_glyph(65, 2, 3, -2, 1, 5),
+        ];
+        let atlas = build_atlas(glyphs, 0);
+
+        assert_eq!((atlas.columns, atlas.rows), (2, 1));
+        assert_eq!((atlas.slot_glyph_size, atlas.padding, atlas.glyph_size), (7, IMPORT_PADDING, 6));
+        assert_eq!((atlas.width, atlas.height), (14, 7));
+        assert_eq!(atlas.image.len(), 4 * 14 * 7);
+
+        // `A` starts at the left edge of its slot, and `B` three pixels further in, as far
+        // apart as their offsets.
+        let ink: Vec<(usize, usize)> = (0..atlas.height)
+            .flat_map(|y| (0..atlas.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| alpha(&atlas, x, y) != 0)
+            .collect();
+        assert_eq!(ink, vec![
+            (0, 0), (1, 0), (10, 0), (11, 0), (12, 0),
+            (0, 1), (1, 1), (10, 1), (11, 1), (12, 1),
+            (0, 2), (1, 2),
+        ]);
+
+        let a = atlas.glyph_metadata[&65];
+        assert_eq!((a.row, a.column), (0, 0));
+        assert_eq!((a.x_min, a.y_min), (0.0, 0.0));
+        assert_eq!((a.width, a.height, a.y_offset), (5.0 / 7.0, 3.0 / 7.0, 1.0 / 7.0));
+        let b = atlas.glyph_metadata[&66];
+        assert_eq!((b.row, b.column), (0, 1));
+        assert_eq!((b.x_min, b.y_min), (0.5, 0.0));
+        assert_eq!((b.width, b.height, b.y_offset), (4.0 / 7.0, 2.0 / 7.0, 0.0));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::encoding;
+
+use serde_json::json;
+
+use std::fmt::Write;
+use std::path::Path;
+
+
+/// The code points in the atlas grouped into runs of consecutive code points, as
+/// `(first, last)` pairs in ascending order.
+pub fn code_point_ranges(atlas: &bmfa::BitmapFontAtlas) -> Vec<(usize, usize)> {
+    let mut code_points: Vec<usize> = atlas.glyph_metadata.keys().cloned().collect();
+    code_points.sort();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for code_point in code_points {
+        match ranges.last_mut() {
+            Some(range) if range.1 + 1 == code_point => range.1 = code_point,
+            _ => ranges.push((code_point, code_point)),
+        }
+    }
+
+    ranges
+}
+
+/// The pixel format of the atlas image. The loader always decodes the image to
+/// 8 bit RGBA, so the interesting part is how the glyphs are encoded in it.
+fn image_format(atlas: &bmfa::BitmapFontAtlas) -> String {
+    format!("RGBA8, {} glyphs", encoding::detect_glyph_encoding(atlas))
+}
+
+/// Describe the atlas metadata in human readable form, one field per line.
+pub fn describe(path: &Path, atlas: &bmfa::BitmapFontAtlas) -> String {
+    let ranges: Vec<String> = code_point_ranges(atlas).iter()
+        .map(|&(first, last)| {
+            if first == last {
+                format!("U+{:04X}", first)
+            } else {
+                format!("U+{:04X}-U+{:04X}", first, last)
+            }
+        })
+        .collect();
+
+    let mut description = String::new();
+    writeln!(description, "path: {}", path.display()).unwrap();
+    writeln!(description, "width: {}", atlas.width).unwrap();
+    writeln!(description, "height: {}", atlas.height).unwrap();
+    writeln!(description, "rows: {}", atlas.rows).unwrap();
+    writeln!(description, "columns: {}", atlas.columns).unwrap();
+    writeln!(description, "padding: {}", atlas.padding).unwrap();
+    writeln!(description, "slot_glyph_size: {}", atlas.slot_glyph_size).unwrap();
+    writeln!(description, "glyph_size: {}", atlas.glyph_size).unwrap();
+    writeln!(description, "origin: {:?}", atlas.origin).unwrap();
+    writeln!(description, "glyph count: {}", atlas.glyph_metadata.len()).unwrap();
+    writeln!(description, "code points: {}", ranges.join(", ")).unwrap();
+    write!(description, "image format: {}", image_format(atlas)).unwrap();
+
+    description
+}
+
+/// Describe the atlas metadata as JSON for build scripts. The code point ranges are
+/// inclusive at both ends.
+pub fn describe_json(path: &Path, atlas: &bmfa::BitmapFontAtlas) -> serde_json::Value {
+    let ranges: Vec<serde_json::Value> = code_point_ranges(atlas).iter()
+        .map(|&(first, last)| json!({ "first": first, "last": last }))
+        .collect();
+
+    json!({
+        "path": path.to_string_lossy(),
+        "width": atlas.width,
+        "height": atlas.height,
+        "rows": atlas.rows,
+        "columns": atlas.columns,
+        "padding": atlas.padding,
+        "slot_glyph_size": atlas.slot_glyph_size,
+        "glyph_size": atlas.glyph_size,
+        "origin": format!("{:?}", atlas.origin),
+        "glyph_count": atlas.glyph_metadata.len(),
+        "code_point_ranges": ranges,
+        "image_format": image_format(atlas),
+    })
+}
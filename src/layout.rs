@@ -0,0 +1,216 @@
+use std::mem;
+
+
+/// The vertical gap between lines of text in normalized device coordinates.
+const LINE_SPACING: f32 = 0.05;
+
+
+/// Where a block of text starts on the screen, where it wraps, and how large its
+/// glyphs are. Positions are in normalized device coordinates.
+#[derive(Copy, Clone, Debug)]
+pub struct TextPlacement {
+    pub start_at_x: f32,
+    pub start_at_y: f32,
+    pub end_at_x: f32,
+    pub scale_px: f32,
+}
+
+impl TextPlacement {
+    pub fn new(start_at_x: f32, start_at_y: f32, end_at_x: f32, scale_px: f32) -> TextPlacement {
+        TextPlacement {
+            start_at_x: start_at_x,
+            start_at_y: start_at_y,
+            end_at_x: end_at_x,
+            scale_px: scale_px,
+        }
+    }
+}
+
+/// A glyph placed on the screen by the text layout. The quad covers the whole atlas
+/// slot for the glyph, while the advance is how far the pen moved past it.
+#[derive(Copy, Clone, Debug)]
+pub struct PlacedGlyph {
+    pub code_point: usize,
+    /// The line of text the glyph is on, counting wrapped lines.
+    pub line: usize,
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub advance: f32,
+}
+
+impl PlacedGlyph {
+    /// Determine whether a point lies in the part of the line this glyph advanced over.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.left && x < self.left + self.advance && y <= self.top && y >= self.bottom
+    }
+}
+
+/// The vertices and texture coordinates for a string of text, along with where
+/// each glyph landed on the screen.
+#[derive(Clone, Debug)]
+pub struct TextLayout {
+    pub points: Vec<f32>,
+    pub texcoords: Vec<f32>,
+    pub glyphs: Vec<PlacedGlyph>,
+    /// Where the pen was before each character of the string, and after the last one.
+    pub pen_positions: Vec<(f32, f32)>,
+}
+
+impl TextLayout {
+    /// The number of vertices in the layout.
+    pub fn point_count(&self) -> usize {
+        self.points.len() / 2
+    }
+
+    /// The size in bytes of the vertex data for the layout.
+    pub fn vertex_bytes(&self) -> usize {
+        mem::size_of::<f32>() * (self.points.len() + self.texcoords.len())
+    }
+
+    /// Find the glyph under a point in normalized device coordinates.
+    pub fn glyph_at(&self, x: f32, y: f32) -> Option<&PlacedGlyph> {
+        self.glyphs.iter().find(|glyph| glyph.contains(x, y))
+    }
+}
+
+/// Parse the width or height of the screen text is laid out on, in pixels. Positions
+/// are divided by the screen size, so it cannot be zero.
+pub fn parse_screen_size(st: &str) -> Result<u32, String> {
+    match st.trim().parse() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("Invalid screen size `{}`. Expected a whole number of pixels greater than zero.", st)),
+    }
+}
+
+/// Look up the glyph for a character in the atlas, or `None` if the atlas has no
+/// glyph for it.
+pub fn glyph_metadata(atlas: &bmfa::BitmapFontAtlas, ch: char) -> Option<bmfa::GlyphMetadata> {
+    atlas.glyph_metadata.get(&(ch as usize)).cloned()
+}
+
+/// The characters in a string the atlas has no glyph for, along with their index in
+/// the string. Newlines start a new line instead of being drawn, so they need no glyph.
+pub fn missing_glyphs(atlas: &bmfa::BitmapFontAtlas, st: &str) -> Vec<(usize, char)> {
+    st.chars()
+        .enumerate()
+        .filter(|&(_, ch)| ch != '\n' && glyph_metadata(atlas, ch).is_none())
+        .collect()
+}
+
+/// Lay out a string of text on a screen `width` by `height` pixels in size. Lines
+/// wrap when they pass the end of the placement, and start over at each newline.
+/// Characters the atlas has no glyph for are skipped.
+pub fn layout_text(
+    atlas: &bmfa::BitmapFontAtlas, placement: TextPlacement,
+    width: u32, height: u32, st: &str) -> TextLayout {
+
+    let scale_px = placement.scale_px;
+    let glyph_count = st.chars().filter(|&ch| ch != '\n' && glyph_metadata(atlas, ch).is_some()).count();
+
+    let mut points = vec![0.0; 12 * glyph_count];
+    let mut texcoords = vec![0.0; 12 * glyph_count];
+    let mut glyphs = Vec::with_capacity(glyph_count);
+    let mut pen_positions = Vec::with_capacity(st.chars().count() + 1);
+    let mut at_x = placement.start_at_x;
+    let end_at_x = placement.end_at_x;
+    let mut at_y = placement.start_at_y;
+
+    let mut i = 0;
+    let mut line = 0;
+    for ch_i in st.chars() {
+        pen_positions.push((at_x, at_y));
+        if ch_i == '\n' {
+            at_x = placement.start_at_x;
+            at_y -= LINE_SPACING + scale_px / (height as f32);
+            line += 1;
+            continue;
+        }
+
+        let metadata_i = match glyph_metadata(atlas, ch_i) {
+            Some(metadata) => metadata,
+            None => continue,
+        };
+        let atlas_col = metadata_i.column;
+        let atlas_row = metadata_i.row;
+
+        let s = (atlas_col as f32) * (1.0 / (atlas.columns as f32));
+        let t = ((atlas_row + 1) as f32) * (1.0 / (atlas.rows as f32));
+
+        let x_pos = at_x;
+        let y_pos = at_y - (scale_px / (height as f32)) * metadata_i.y_offset;
+
+        let advance = metadata_i.width * (scale_px / width as f32);
+        let glyph_line = line;
+        at_x += advance;
+        if at_x >= end_at_x {
+            at_x = placement.start_at_x;
+            at_y -= LINE_SPACING + metadata_i.height * (scale_px / height as f32);
+            line += 1;
+        }
+
+        points[12 * i]     = x_pos;
+        points[12 * i + 1] = y_pos;
+        points[12 * i + 2] = x_pos;
+        points[12 * i + 3] = y_pos - scale_px / (height as f32);
+        points[12 * i + 4] = x_pos + scale_px / (width as f32);
+        points[12 * i + 5] = y_pos - scale_px / (height as f32);
+
+        points[12 * i + 6]  = x_pos + scale_px / (width as f32);
+        points[12 * i + 7]  = y_pos - scale_px / (height as f32);
+        points[12 * i + 8]  = x_pos + scale_px / (width as f32);
+        points[12 * i + 9]  = y_pos;
+        points[12 * i + 10] = x_pos;
+        points[12 * i + 11] = y_pos;
+
+        texcoords[12 * i]     = s;
+        texcoords[12 * i + 1] = 1.0 - t + 1.0 / (atlas.rows as f32);
+        texcoords[12 * i + 2] = s;
+        texcoords[12 * i + 3] = 1.0 - t;
+        texcoords[12 * i + 4] = s + 1.0 / (atlas.columns as f32);
+        texcoords[12 * i + 5] = 1.0 - t;
+
+        texcoords[12 * i + 6]  = s + 1.0 / (atlas.columns as f32);
+        texcoords[12 * i + 7]  = 1.0 - t;
+        texcoords[12 * i + 8]  = s + 1.0 / (atlas.columns as f32);
+        texcoords[12 * i + 9]  = 1.0 - t + 1.0 / (atlas.rows as f32);
+        texcoords[12 * i + 10] = s;
+        texcoords[12 * i + 11] = 1.0 - t + 1.0 / (atlas.rows as f32);
+
+        glyphs.push(PlacedGlyph {
+            code_point: ch_i as usize,
+            line: glyph_line,
+            left: x_pos,
+            top: y_pos,
+            right: x_pos + scale_px / (width as f32),
+            bottom: y_pos - scale_px / (height as f32),
+            advance: advance,
+        });
+
+        i += 1;
+    }
+    pen_positions.push((at_x, at_y));
+
+    TextLayout {
+        points: points,
+        texcoords: texcoords,
+        glyphs: glyphs,
+        pen_positions: pen_positions,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_sizes_are_positive() {
+        assert_eq!(parse_screen_size("1024"), Ok(1024));
+        assert_eq!(parse_screen_size(" 576 "), Ok(576));
+        for &size in ["0", "-1", "", "wide", "12.5"].iter() {
+            assert!(parse_screen_size(size).is_err(), "{} should not be a valid size", size);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::gl;
+use crate::gl::types::{GLfloat, GLint, GLsizeiptr, GLuint, GLvoid};
+use crate::gl_help as glh;
+
+use std::io;
+use std::mem;
+use std::ptr;
+
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const LINES_VERT: &str = include_str!("../shaders/330/fontview_lines.vert.glsl");
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const LINES_FRAG: &str = include_str!("../shaders/330/fontview_lines.frag.glsl");
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const LINES_VERT: &str = include_str!("../shaders/420/fontview_lines.vert.glsl");
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const LINES_FRAG: &str = include_str!("../shaders/420/fontview_lines.frag.glsl");
+
+
+/// A list of colored line segments and filled rectangles in normalized device
+/// coordinates, waiting to be written to the GPU.
+#[derive(Clone, Debug)]
+pub struct LineBatch {
+    points: Vec<GLfloat>,
+    colors: Vec<GLfloat>,
+    fill_points: Vec<GLfloat>,
+    fill_colors: Vec<GLfloat>,
+}
+
+impl LineBatch {
+    pub fn new() -> LineBatch {
+        LineBatch {
+            points: vec![],
+            colors: vec![],
+            fill_points: vec![],
+            fill_colors: vec![],
+        }
+    }
+
+    /// The number of line vertices in the batch.
+    pub fn vertex_count(&self) -> usize {
+        self.points.len() / 2
+    }
+
+    /// The number of filled triangle vertices in the batch.
+    pub fn fill_vertex_count(&self) -> usize {
+        self.fill_points.len() / 2
+    }
+
+    pub fn push_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        self.points.extend_from_slice(&[x0, y0, x1, y1]);
+        for _ in 0..2 {
+            self.colors.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Push the outline of the axis aligned rectangle with corners `(x0, y0)` and `(x1, y1)`.
+    pub fn push_rect(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        self.push_line(x0, y0, x1, y0, color);
+        self.push_line(x1, y0, x1, y1, color);
+        self.push_line(x1, y1, x0, y1, color);
+        self.push_line(x0, y1, x0, y0, color);
+    }
+
+    /// Push a filled axis aligned rectangle with corners `(x0, y0)` and `(x1, y1)`.
+    /// Filled rectangles are drawn underneath the lines.
+    pub fn push_filled_rect(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: Color) {
+        let (left, right) = (f32::min(x0, x1), f32::max(x0, x1));
+        let (bottom, top) = (f32::min(y0, y1), f32::max(y0, y1));
+        // Counter clockwise winding so the rectangle survives back face culling.
+        self.fill_points.extend_from_slice(&[
+            left, top, left, bottom, right, bottom,
+            right, bottom, right, top, left, top,
+        ]);
+        for _ in 0..6 {
+            self.fill_colors.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+}
+
+/// The GPU buffers holding a batch of lines. The filled triangles are stored first,
+/// followed by the line segments.
+#[derive(Copy, Clone, Debug)]
+pub struct GLLineWriter {
+    pub vao: GLuint,
+    points_vbo: GLuint,
+    colors_vbo: GLuint,
+    fill_vertex_count: usize,
+    vertex_count: usize,
+}
+
+impl GLLineWriter {
+    /// Upload a line batch to the GPU, replacing the previous one.
+    pub fn write(&mut self, batch: &LineBatch) -> io::Result<usize> {
+        let mut points = Vec::with_capacity(batch.fill_points.len() + batch.points.len());
+        points.extend_from_slice(&batch.fill_points);
+        points.extend_from_slice(&batch.points);
+        let mut colors = Vec::with_capacity(batch.fill_colors.len() + batch.colors.len());
+        colors.extend_from_slice(&batch.fill_colors);
+        colors.extend_from_slice(&batch.colors);
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.points_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER, (mem::size_of::<GLfloat>() * points.len()) as GLsizeiptr,
+                points.as_ptr() as *const GLvoid, gl::DYNAMIC_DRAW
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.colors_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER, (mem::size_of::<GLfloat>() * colors.len()) as GLsizeiptr,
+                colors.as_ptr() as *const GLvoid, gl::DYNAMIC_DRAW
+            );
+        }
+        self.fill_vertex_count = batch.fill_vertex_count();
+        self.vertex_count = batch.vertex_count();
+
+        let bytes_written = mem::size_of::<GLfloat>() * (points.len() + colors.len());
+
+        Ok(bytes_written)
+    }
+}
+
+pub fn create_line_writer() -> GLLineWriter {
+    let mut points_vbo = 0;
+    unsafe {
+        gl::GenBuffers(1, &mut points_vbo);
+    }
+    assert!(points_vbo > 0);
+
+    let mut colors_vbo = 0;
+    unsafe {
+        gl::GenBuffers(1, &mut colors_vbo);
+    }
+    assert!(colors_vbo > 0);
+
+    let mut vao = 0;
+    unsafe {
+        gl::GenVertexArrays(1, &mut vao);
+    }
+    assert!(vao > 0);
+
+    unsafe {
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, points_vbo);
+        gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+        gl::EnableVertexAttribArray(0);
+        gl::BindBuffer(gl::ARRAY_BUFFER, colors_vbo);
+        gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, 0, ptr::null());
+        gl::EnableVertexAttribArray(1);
+    }
+
+    GLLineWriter {
+        vao: vao,
+        points_vbo: points_vbo,
+        colors_vbo: colors_vbo,
+        fill_vertex_count: 0,
+        vertex_count: 0,
+    }
+}
+
+/// The line shader program along with the locations of its uniforms.
+#[derive(Copy, Clone, Debug)]
+pub struct LineShader {
+    sp: GLuint,
+    view_scale_loc: GLint,
+    view_translation_loc: GLint,
+}
+
+pub fn create_line_shader(context: &glh::GLState) -> LineShader {
+    let mut vert_reader = io::Cursor::new(LINES_VERT);
+    let mut frag_reader = io::Cursor::new(LINES_FRAG);
+    let sp = glh::create_program_from_reader(
+        context,
+        &mut vert_reader, "fontview_lines.vert.glsl",
+        &mut frag_reader, "fontview_lines.frag.glsl",
+    ).unwrap();
+    assert!(sp > 0);
+
+    let view_scale_loc = unsafe {
+        gl::GetUniformLocation(sp, glh::gl_str("view_scale").as_ptr())
+    };
+    assert!(view_scale_loc > -1);
+
+    let view_translation_loc = unsafe {
+        gl::GetUniformLocation(sp, glh::gl_str("view_translation").as_ptr())
+    };
+    assert!(view_translation_loc > -1);
+
+    LineShader {
+        sp: sp,
+        view_scale_loc: view_scale_loc,
+        view_translation_loc: view_translation_loc,
+    }
+}
+
+/// Draw the lines and filled rectangles last written to the line writer through a camera.
+pub fn draw_lines(shader: &LineShader, writer: &GLLineWriter, camera: &Camera) {
+    unsafe {
+        gl::UseProgram(shader.sp);
+        gl::Uniform2f(shader.view_scale_loc, camera.zoom, camera.zoom);
+        gl::Uniform2f(shader.view_translation_loc, camera.pan_x, camera.pan_y);
+        gl::BindVertexArray(writer.vao);
+        gl::DrawArrays(gl::TRIANGLES, 0, writer.fill_vertex_count as GLint);
+        gl::DrawArrays(gl::LINES, writer.fill_vertex_count as GLint, writer.vertex_count as GLint);
+    }
+}
@@ -3,35 +3,78 @@ extern crate log;
 extern crate stb_image;
 extern crate bmfa;
 extern crate structopt;
+extern crate serde_json;
 
 mod gl {
     include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
 }
 
+mod atlas_view;
+mod banner;
+mod camera;
+mod charset;
+mod check;
+mod color;
+mod coverage;
+mod debug_overlay;
+mod editor;
+mod encoding;
+mod fnt;
+mod generate;
 mod gl_help;
+mod help;
+mod hover;
+mod import;
+mod info;
+mod layout;
+mod lines;
+mod panel;
+mod perf;
+mod screenshot;
+#[cfg(test)]
+mod test_atlas;
+mod text_help;
+mod texture;
+mod validate;
+mod vertex_export;
+mod watch;
 
 
 use crate::gl::types::{
     GLfloat, GLint, GLsizeiptr, GLuint, GLvoid
 };
 
+use crate::atlas_view::{AtlasLayout, AtlasOverlayColors};
+use crate::camera::{Camera, CameraController};
+use crate::charset::CharacterSet;
+use crate::color::Color;
+use crate::debug_overlay::{DebugOverlayColors, DebugOverlays};
+use crate::editor::{EditResult, TextEditor};
+use crate::encoding::GlyphEncoding;
+use crate::fnt::ExportFormat;
+use crate::generate::{AtlasOrigin, CodePoints};
 use crate::gl_help as glh;
+use crate::import::ImportOptions;
+use crate::layout::{TextLayout, TextPlacement};
+use crate::lines::LineBatch;
+use crate::panel::Panel;
+use crate::perf::{FrameStats, HudMode};
+use crate::texture::{MagFilter, MinFilter, TextureFormat, TextureSettings, WrapMode};
+use crate::vertex_export::VertexFormat;
+use crate::watch::FileWatcher;
 
 use glfw::{Action, Context, Key};
 use std::fmt;
+use std::fs;
 use std::io;
 use std::mem;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::ptr;
 use std::str;
 use structopt::StructOpt;
 
 
-// OpenGL extension constants.
-const GL_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FE;
-const GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
-
 const DEFAULT_TEXT: &str = "\
 Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor \
 incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis \
@@ -48,90 +91,22 @@ semper conubia per sollicitudin ultricies, vitae himenaeos senectus dapibus cubi
 imperdiet taciti aptent ante, in metus a hac magnis natoque ullamcorper turpis.";
 
 
+/// The window and the text on screen. The text is laid out against the window size in
+/// screen coordinates, while the viewport covers the framebuffer, which is larger than
+/// the window on high DPI displays.
 struct App {
     gl: glh::GLState,
     writer: GLTextWriter,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
 }
 
-fn text_to_screen(app: &mut App, atlas: &bmfa::BitmapFontAtlas, placement: TextPlacement, st: &str) -> io::Result<(usize, usize)> {
-    let scale_px = placement.scale_px;
-    let height = app.gl.height;
-    let width = app.gl.width;
-    let line_spacing = 0.05;
-
-    let mut points = vec![0.0; 12 * st.len()];
-    let mut texcoords = vec![0.0; 12 * st.len()];
-    let mut at_x = placement.start_at_x;
-    let end_at_x = 0.95;
-    let mut at_y = placement.start_at_y;
-
-    for (i, ch_i) in st.chars().enumerate() {
-        let metadata_i = atlas.glyph_metadata[&(ch_i as usize)];
-        let atlas_col = metadata_i.column;
-        let atlas_row = metadata_i.row;
-
-        let s = (atlas_col as f32) * (1.0 / (atlas.columns as f32));
-        let t = ((atlas_row + 1) as f32) * (1.0 / (atlas.rows as f32));
-
-        let x_pos = at_x;
-        let y_pos = at_y - (scale_px / (height as f32)) * metadata_i.y_offset;
-
-        at_x += metadata_i.width * (scale_px / width as f32);
-        if at_x >= end_at_x {
-            at_x = placement.start_at_x;
-            at_y -= line_spacing + metadata_i.height * (scale_px / height as f32);
-        }
-
-        points[12 * i]     = x_pos;
-        points[12 * i + 1] = y_pos;
-        points[12 * i + 2] = x_pos;
-        points[12 * i + 3] = y_pos - scale_px / (height as f32);
-        points[12 * i + 4] = x_pos + scale_px / (width as f32);
-        points[12 * i + 5] = y_pos - scale_px / (height as f32);
-
-        points[12 * i + 6]  = x_pos + scale_px / (width as f32);
-        points[12 * i + 7]  = y_pos - scale_px / (height as f32);
-        points[12 * i + 8]  = x_pos + scale_px / (width as f32);
-        points[12 * i + 9]  = y_pos;
-        points[12 * i + 10] = x_pos;
-        points[12 * i + 11] = y_pos;
-
-        texcoords[12 * i]     = s;
-        texcoords[12 * i + 1] = 1.0 - t + 1.0 / (atlas.rows as f32);
-        texcoords[12 * i + 2] = s;
-        texcoords[12 * i + 3] = 1.0 - t;
-        texcoords[12 * i + 4] = s + 1.0 / (atlas.columns as f32);
-        texcoords[12 * i + 5] = 1.0 - t;
-
-        texcoords[12 * i + 6]  = s + 1.0 / (atlas.columns as f32);
-        texcoords[12 * i + 7]  = 1.0 - t;
-        texcoords[12 * i + 8]  = s + 1.0 / (atlas.columns as f32);
-        texcoords[12 * i + 9]  = 1.0 - t + 1.0 / (atlas.rows as f32);
-        texcoords[12 * i + 10] = s;
-        texcoords[12 * i + 11] = 1.0 - t + 1.0 / (atlas.rows as f32);
-    }
-
-    let point_count = 6 * st.len();
-    app.writer.write(&points, &texcoords)?;
-
-    Ok((st.len(), point_count))
-}
-
-#[derive(Copy, Clone, Debug)]
-struct TextPlacement {
-    start_at_x: f32,
-    start_at_y: f32,
-    scale_px: f32,
-}
+/// Lay out a string of text for the current window size and load it onto the GPU.
+fn text_to_screen(app: &mut App, atlas: &bmfa::BitmapFontAtlas, placement: TextPlacement, st: &str) -> io::Result<TextLayout> {
+    let text_layout = layout::layout_text(atlas, placement, app.gl.width, app.gl.height, st);
+    app.writer.write(&text_layout.points, &text_layout.texcoords)?;
 
-impl TextPlacement {
-    fn new(start_at_x: f32, start_at_y: f32, scale_px: f32) -> TextPlacement {
-        TextPlacement {
-            start_at_x: start_at_x,
-            start_at_y: start_at_y,
-            scale_px: scale_px,
-        }
-    }
+    Ok(text_layout)
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -170,83 +145,450 @@ impl GLTextWriter {
     }
 }
 
+/// The shader sources embedded in the binary. macOS and Windows only guarantee an
+/// OpenGL 3.3 core context, so they get the GLSL 330 shaders.
 #[cfg(any(target_os = "macos", target_os = "windows"))]
-fn create_shaders(app: &mut App) -> (GLuint, GLint) {
-    let mut vert_reader = io::Cursor::new(include_str!("../shaders/330/fontview.vert.glsl"));
-    let mut frag_reader = io::Cursor::new(include_str!("../shaders/330/fontview.frag.glsl"));
-    let sp = glh::create_program_from_reader(
-        &app.gl,
-        &mut vert_reader, "fontview.vert.glsl",
-        &mut frag_reader, "fontview.frag.glsl",
-    ).unwrap();
-    assert!(sp > 0);
+mod shader_src {
+    pub const TEXT_VERT: &str = include_str!("../shaders/330/fontview.vert.glsl");
+    pub const TEXT_FRAG: &str = include_str!("../shaders/330/fontview.frag.glsl");
+    pub const SDF_FRAG: &str = include_str!("../shaders/330/fontview_sdf.frag.glsl");
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod shader_src {
+    pub const TEXT_VERT: &str = include_str!("../shaders/420/fontview.vert.glsl");
+    pub const TEXT_FRAG: &str = include_str!("../shaders/420/fontview.frag.glsl");
+    pub const SDF_FRAG: &str = include_str!("../shaders/420/fontview_sdf.frag.glsl");
+}
 
-    let sp_text_color_loc = unsafe {
-        gl::GetUniformLocation(sp, glh::gl_str("text_color").as_ptr())
+/// Look up the location of a uniform in the shader program. The location is `-1` if
+/// the program does not use the uniform, which custom shaders are free to do since
+/// OpenGL ignores values set at location `-1`.
+fn uniform_location(sp: GLuint, name: &str) -> GLint {
+    let loc = unsafe {
+        gl::GetUniformLocation(sp, glh::gl_str(name).as_ptr())
     };
-    assert!(sp_text_color_loc > 0);
 
-    (sp, sp_text_color_loc)
+    loc
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
-fn create_shaders(app: &mut App) -> (GLuint, GLint) {
-    let mut vert_reader = io::Cursor::new(include_str!("../shaders/420/fontview.vert.glsl"));
-    let mut frag_reader = io::Cursor::new(include_str!("../shaders/420/fontview.frag.glsl"));
+/// The text shader program along with the locations of its uniforms.
+#[derive(Copy, Clone, Debug)]
+struct TextShader {
+    sp: GLuint,
+    text_color_loc: GLint,
+    offset_loc: GLint,
+    view_scale_loc: GLint,
+    view_translation_loc: GLint,
+}
+
+impl TextShader {
+    fn new(sp: GLuint) -> TextShader {
+        TextShader {
+            sp: sp,
+            text_color_loc: uniform_location(sp, "text_color"),
+            offset_loc: uniform_location(sp, "offset"),
+            view_scale_loc: uniform_location(sp, "view_scale"),
+            view_translation_loc: uniform_location(sp, "view_translation"),
+        }
+    }
+
+    /// Use the shader program and apply the camera's view transform.
+    fn use_with_camera(&self, camera: &Camera) {
+        unsafe {
+            gl::UseProgram(self.sp);
+            gl::Uniform2f(self.view_scale_loc, camera.zoom, camera.zoom);
+            gl::Uniform2f(self.view_translation_loc, camera.pan_x, camera.pan_y);
+        }
+    }
+}
+
+/// The signed distance field text shader program along with the locations of its uniforms.
+#[derive(Copy, Clone, Debug)]
+struct SdfShader {
+    text: TextShader,
+    msdf_loc: GLint,
+    threshold_loc: GLint,
+    softness_loc: GLint,
+    outline_color_loc: GLint,
+    outline_width_loc: GLint,
+    glow_color_loc: GLint,
+    glow_width_loc: GLint,
+    premultiplied_loc: GLint,
+}
+
+impl SdfShader {
+    fn new(sp: GLuint) -> SdfShader {
+        SdfShader {
+            text: TextShader::new(sp),
+            msdf_loc: uniform_location(sp, "msdf"),
+            threshold_loc: uniform_location(sp, "threshold"),
+            softness_loc: uniform_location(sp, "softness"),
+            outline_color_loc: uniform_location(sp, "outline_color"),
+            outline_width_loc: uniform_location(sp, "outline_width"),
+            glow_color_loc: uniform_location(sp, "glow_color"),
+            glow_width_loc: uniform_location(sp, "glow_width"),
+            premultiplied_loc: uniform_location(sp, "premultiplied"),
+        }
+    }
+}
+
+/// The shader programs used to draw text, one for each kind of glyph image.
+#[derive(Copy, Clone, Debug)]
+struct TextShaders {
+    bitmap: TextShader,
+    sdf: SdfShader,
+}
+
+impl TextShaders {
+    fn delete(&self) {
+        unsafe {
+            gl::DeleteProgram(self.bitmap.sp);
+            gl::DeleteProgram(self.sdf.text.sp);
+        }
+    }
+}
+
+/// The custom shader files given on the command line.
+#[derive(Clone, Debug, Default)]
+struct ShaderPaths {
+    vertex: Option<PathBuf>,
+    fragment: Option<PathBuf>,
+}
+
+impl ShaderPaths {
+    fn from_opt(opt: &Opt) -> ShaderPaths {
+        ShaderPaths {
+            vertex: opt.vertex_shader_path.clone(),
+            fragment: opt.fragment_shader_path.clone(),
+        }
+    }
+}
+
+/// Read a shader from a custom shader file if there is one, or use the embedded source.
+/// Returns the file name to report in the shader logs along with the source.
+fn shader_source(
+    path: &Option<PathBuf>, file_name: &str,
+    embedded: &str) -> Result<(String, String), glh::ShaderCompilationError> {
+
+    match *path {
+        Some(ref path) => {
+            let disp = path.display().to_string();
+            match fs::read_to_string(path) {
+                Ok(source) => Ok((disp, source)),
+                Err(_) => Err(glh::ShaderCompilationError::ShaderNotFound(disp)),
+            }
+        }
+        None => Ok((String::from(file_name), String::from(embedded))),
+    }
+}
+
+fn create_text_program(
+    app: &App, vert: &(String, String), frag: &(String, String)) -> Result<GLuint, glh::ShaderCompilationError> {
+
+    let mut vert_reader = io::Cursor::new(vert.1.as_bytes());
+    let mut frag_reader = io::Cursor::new(frag.1.as_bytes());
     let sp = glh::create_program_from_reader(
         &app.gl,
-        &mut vert_reader, "fontview.vert.glsl",
-        &mut frag_reader, "fontview.frag.glsl",
-    ).unwrap();
+        &mut vert_reader, &vert.0,
+        &mut frag_reader, &frag.0,
+    )?;
     assert!(sp > 0);
 
-    let sp_text_color_loc = unsafe { 
-        gl::GetUniformLocation(sp, glh::gl_str("text_color").as_ptr())
+    Ok(sp)
+}
+
+/// Compile the text shader programs. A custom vertex shader replaces the embedded one in
+/// both programs, and a custom fragment shader replaces the embedded one for every kind
+/// of glyph image, so it is used whatever the atlas encoding is.
+fn create_shaders(app: &App, paths: &ShaderPaths) -> Result<TextShaders, glh::ShaderCompilationError> {
+    let vert = shader_source(&paths.vertex, "fontview.vert.glsl", shader_src::TEXT_VERT)?;
+    let frag = shader_source(&paths.fragment, "fontview.frag.glsl", shader_src::TEXT_FRAG)?;
+    let sdf_frag = shader_source(&paths.fragment, "fontview_sdf.frag.glsl", shader_src::SDF_FRAG)?;
+
+    let sp = create_text_program(app, &vert, &frag)?;
+    let sdf_sp = match create_text_program(app, &vert, &sdf_frag) {
+        Ok(val) => val,
+        Err(e) => {
+            unsafe {
+                gl::DeleteProgram(sp);
+            }
+            return Err(e);
+        }
     };
-    assert!(sp_text_color_loc > 0);
 
-    (sp, sp_text_color_loc)
+    Ok(TextShaders {
+        bitmap: TextShader::new(sp),
+        sdf: SdfShader::new(sdf_sp),
+    })
+}
+
+/// The parameters for rendering distance field glyphs.
+#[derive(Copy, Clone, Debug)]
+struct DistanceFieldSettings {
+    encoding: GlyphEncoding,
+    /// The distance value at the glyph edge.
+    threshold: f32,
+    /// The width of the antialiased edge in screen pixels.
+    softness: f32,
+}
+
+impl DistanceFieldSettings {
+    fn from_opt(opt: &Opt, atlas: &bmfa::BitmapFontAtlas) -> DistanceFieldSettings {
+        let encoding = if opt.sdf {
+            GlyphEncoding::Sdf
+        } else if opt.msdf {
+            GlyphEncoding::Msdf
+        } else if opt.bitmap {
+            GlyphEncoding::Bitmap
+        } else {
+            encoding::detect_glyph_encoding(atlas)
+        };
+
+        DistanceFieldSettings {
+            encoding: encoding,
+            threshold: opt.sdf_threshold,
+            softness: opt.sdf_softness,
+        }
+    }
+}
+
+/// The drop shadow, outline and glow settings for the rendered text. Offsets and widths
+/// are measured in screen pixels. The glow is only drawn for distance field atlases.
+#[derive(Copy, Clone, Debug)]
+struct TextEffects {
+    shadow_enabled: bool,
+    shadow_offset_x: f32,
+    shadow_offset_y: f32,
+    shadow_color: Color,
+    outline_enabled: bool,
+    outline_width: f32,
+    outline_color: Color,
+    glow_enabled: bool,
+    glow_width: f32,
+    glow_color: Color,
+}
+
+impl TextEffects {
+    fn from_opt(opt: &Opt) -> TextEffects {
+        TextEffects {
+            shadow_enabled: opt.shadow,
+            shadow_offset_x: opt.shadow_offset_x,
+            shadow_offset_y: opt.shadow_offset_y,
+            shadow_color: opt.shadow_color,
+            outline_enabled: opt.outline,
+            outline_width: opt.outline_width,
+            outline_color: opt.outline_color,
+            glow_enabled: opt.glow,
+            glow_width: opt.glow_width,
+            glow_color: opt.glow_color,
+        }
+    }
+}
+
+/// The number of offset copies of the text drawn to build up the outline.
+const OUTLINE_SAMPLES: usize = 8;
+
+/// How colors are blended into the framebuffer.
+#[derive(Copy, Clone, Debug)]
+struct ColorPipeline {
+    /// Store the atlas in an sRGB texture.
+    srgb_texture: bool,
+    /// Write to the framebuffer through `GL_FRAMEBUFFER_SRGB`, so blending happens in linear space.
+    srgb_framebuffer: bool,
+    /// Blend with premultiplied alpha instead of straight alpha.
+    premultiplied_alpha: bool,
+}
+
+impl ColorPipeline {
+    fn from_opt(opt: &Opt) -> ColorPipeline {
+        ColorPipeline {
+            srgb_texture: opt.srgb_texture,
+            srgb_framebuffer: opt.srgb_framebuffer,
+            premultiplied_alpha: opt.premultiplied_alpha,
+        }
+    }
+
+    /// Convert a color picked in sRGB into the color space the shaders write in.
+    fn shader_color(&self, color: Color) -> Color {
+        if self.srgb_framebuffer {
+            color.to_linear()
+        } else {
+            color
+        }
+    }
+
+    /// Convert a color picked in sRGB into a color for the line overlays, which are
+    /// drawn with the same blending as the text.
+    fn overlay_color(&self, color: Color) -> Color {
+        let color = self.shader_color(color);
+        if self.premultiplied_alpha {
+            color.premultiplied()
+        } else {
+            color
+        }
+    }
+
+    /// Set the framebuffer and blending state for the pipeline.
+    fn enable(&self) {
+        unsafe {
+            if self.srgb_framebuffer {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            } else {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+            if self.premultiplied_alpha {
+                gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+            } else {
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            }
+        }
+    }
+}
+
+/// Draw the text currently loaded in the text writer, along with its drop shadow
+/// and outline.
+fn draw_text(
+    app: &App, writer: &GLTextWriter, shaders: &TextShaders, camera: &Camera, effects: &TextEffects,
+    sdf: &DistanceFieldSettings, pipeline: &ColorPipeline, text_color: Color, point_count: usize) {
+
+    let mut effects = *effects;
+    effects.shadow_color = pipeline.shader_color(effects.shadow_color);
+    effects.outline_color = pipeline.shader_color(effects.outline_color);
+    effects.glow_color = pipeline.shader_color(effects.glow_color);
+    let text_color = pipeline.shader_color(text_color);
+
+    match sdf.encoding {
+        GlyphEncoding::Bitmap if pipeline.premultiplied_alpha => {
+            // The atlas is premultiplied on upload, so premultiplying the colors as well
+            // makes the product in the shader premultiplied.
+            effects.shadow_color = effects.shadow_color.premultiplied();
+            effects.outline_color = effects.outline_color.premultiplied();
+            draw_bitmap_text(app, writer, &shaders.bitmap, camera, &effects, text_color.premultiplied(), point_count)
+        }
+        GlyphEncoding::Bitmap => {
+            draw_bitmap_text(app, writer, &shaders.bitmap, camera, &effects, text_color, point_count)
+        }
+        GlyphEncoding::Sdf | GlyphEncoding::Msdf => {
+            // The distance field shader blends the outline and glow itself, so it takes
+            // straight colors and premultiplies its output.
+            shaders.sdf.text.use_with_camera(camera);
+            unsafe {
+                gl::Uniform1i(shaders.sdf.premultiplied_loc, pipeline.premultiplied_alpha as GLint);
+            }
+            draw_sdf_text(app, writer, &shaders.sdf, &effects, sdf, text_color, point_count)
+        }
+    }
 }
 
-/// Load texture image into the GPU.
-fn load_font_texture(atlas: &bmfa::BitmapFontAtlas, wrapping_mode: GLuint) -> Result<GLuint, String> {
-    let mut tex = 0;
+/// Draw coverage bitmap text. The shadow is drawn first, then the outline is built
+/// up by drawing the text several times in a ring around its position, and finally
+/// the text itself is drawn on top.
+fn draw_bitmap_text(
+    app: &App, writer: &GLTextWriter, shader: &TextShader, camera: &Camera,
+    effects: &TextEffects, text_color: Color, point_count: usize) {
+
+    // Convert pixel offsets into normalized device coordinates.
+    let px_to_ndc_x = 2.0 / (app.gl.width as f32);
+    let px_to_ndc_y = 2.0 / (app.gl.height as f32);
+
+    shader.use_with_camera(camera);
     unsafe {
-        gl::GenTextures(1, &mut tex);
+        gl::BindVertexArray(writer.vao);
+    }
+
+    if effects.shadow_enabled {
+        let color = effects.shadow_color;
+        unsafe {
+            gl::Uniform4f(shader.text_color_loc, color.r, color.g, color.b, color.a);
+            gl::Uniform2f(
+                shader.offset_loc,
+                effects.shadow_offset_x * px_to_ndc_x, -effects.shadow_offset_y * px_to_ndc_y
+            );
+            gl::DrawArrays(gl::TRIANGLES, 0, point_count as GLint);
+        }
+    }
+
+    if effects.outline_enabled && effects.outline_width > 0.0 {
+        let color = effects.outline_color;
+        unsafe {
+            gl::Uniform4f(shader.text_color_loc, color.r, color.g, color.b, color.a);
+        }
+        for i in 0..OUTLINE_SAMPLES {
+            let angle = (i as f32) * 2.0 * std::f32::consts::PI / (OUTLINE_SAMPLES as f32);
+            let offset_x = effects.outline_width * angle.cos() * px_to_ndc_x;
+            let offset_y = effects.outline_width * angle.sin() * px_to_ndc_y;
+            unsafe {
+                gl::Uniform2f(shader.offset_loc, offset_x, offset_y);
+                gl::DrawArrays(gl::TRIANGLES, 0, point_count as GLint);
+            }
+        }
     }
-    assert!(tex > 0);
 
     unsafe {
-        gl::ActiveTexture(gl::TEXTURE0);
-        gl::BindTexture(gl::TEXTURE_2D, tex);
-        gl::TexImage2D(
-            gl::TEXTURE_2D, 0, gl::RGBA as i32, atlas.width as i32, atlas.height as i32, 0,
-            gl::RGBA, gl::UNSIGNED_BYTE,
-            atlas.image.as_ptr() as *const GLvoid
-        );
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrapping_mode as GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrapping_mode as GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as GLint);
+        gl::Uniform4f(shader.text_color_loc, text_color.r, text_color.g, text_color.b, text_color.a);
+        gl::Uniform2f(shader.offset_loc, 0.0, 0.0);
+        gl::DrawArrays(gl::TRIANGLES, 0, point_count as GLint);
     }
+}
+
+/// Draw distance field text. The outline and glow are computed from the distance
+/// field in the fragment shader, so only the shadow needs a separate pass. The
+/// shader program must already be in use.
+fn draw_sdf_text(
+    app: &App, writer: &GLTextWriter, shader: &SdfShader, effects: &TextEffects,
+    sdf: &DistanceFieldSettings, text_color: Color, point_count: usize) {
+
+    let px_to_ndc_x = 2.0 / (app.gl.width as f32);
+    let px_to_ndc_y = 2.0 / (app.gl.height as f32);
+    let outline_width = if effects.outline_enabled { effects.outline_width } else { 0.0 };
+    let glow_width = if effects.glow_enabled { effects.glow_width } else { 0.0 };
 
-    let mut max_aniso = 0.0;
     unsafe {
-        gl::GetFloatv(GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_aniso);
-        // Set the maximum!
-        gl::TexParameterf(gl::TEXTURE_2D, GL_TEXTURE_MAX_ANISOTROPY_EXT, max_aniso);
+        gl::BindVertexArray(writer.vao);
+        gl::Uniform1i(shader.msdf_loc, (sdf.encoding == GlyphEncoding::Msdf) as GLint);
+        gl::Uniform1f(shader.threshold_loc, sdf.threshold);
+        gl::Uniform1f(shader.softness_loc, sdf.softness);
+        gl::Uniform1f(shader.glow_width_loc, 0.0);
+    }
+
+    if effects.shadow_enabled {
+        // The shadow is cast by the outlined glyph, so draw the outline in the shadow color too.
+        let color = effects.shadow_color;
+        unsafe {
+            gl::Uniform4f(shader.text.text_color_loc, color.r, color.g, color.b, color.a);
+            gl::Uniform4f(shader.outline_color_loc, color.r, color.g, color.b, color.a);
+            gl::Uniform1f(shader.outline_width_loc, outline_width);
+            gl::Uniform2f(
+                shader.text.offset_loc,
+                effects.shadow_offset_x * px_to_ndc_x, -effects.shadow_offset_y * px_to_ndc_y
+            );
+            gl::DrawArrays(gl::TRIANGLES, 0, point_count as GLint);
+        }
     }
 
-    Ok(tex)
+    let outline_color = effects.outline_color;
+    let glow_color = effects.glow_color;
+    unsafe {
+        gl::Uniform4f(shader.text.text_color_loc, text_color.r, text_color.g, text_color.b, text_color.a);
+        gl::Uniform4f(
+            shader.outline_color_loc, outline_color.r, outline_color.g, outline_color.b, outline_color.a
+        );
+        gl::Uniform1f(shader.outline_width_loc, outline_width);
+        gl::Uniform4f(shader.glow_color_loc, glow_color.r, glow_color.g, glow_color.b, glow_color.a);
+        gl::Uniform1f(shader.glow_width_loc, glow_width);
+        gl::Uniform2f(shader.text.offset_loc, 0.0, 0.0);
+        gl::DrawArrays(gl::TRIANGLES, 0, point_count as GLint);
+    }
 }
 
 fn create_text_placement() -> TextPlacement {
     let start_at_x = -0.95;
     let start_at_y = 0.95;
+    let end_at_x = 0.95;
     let scale_px = 72.0;
 
-    TextPlacement::new(start_at_x, start_at_y, scale_px)
+    TextPlacement::new(start_at_x, start_at_y, end_at_x, scale_px)
 }
 
 fn create_text_writer() -> GLTextWriter {
@@ -268,30 +610,59 @@ fn create_text_writer() -> GLTextWriter {
     }
     assert!(vao > 0);
 
+    unsafe {
+        gl::BindVertexArray(vao);
+        gl::BindBuffer(gl::ARRAY_BUFFER, points_vbo);
+        gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+        gl::EnableVertexAttribArray(0);
+        gl::BindBuffer(gl::ARRAY_BUFFER, texcoords_vbo);
+        gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+        gl::EnableVertexAttribArray(1);
+    }
+
     GLTextWriter::new(vao, points_vbo, texcoords_vbo)
 }
 
-/// The GLFW frame buffer size callback function. This is normally set using
-/// the GLFW `glfwSetFramebufferSizeCallback` function; instead we explicitly
-/// handle window resizing in our state updates on the application side. Run this function
-/// whenever the size of the viewport changes.
-#[inline]
-fn glfw_framebuffer_size_callback(app: &mut App, width: u32, height: u32) {
-    app.gl.width = width;
-    app.gl.height = height;
+/// Track the window and framebuffer sizes from window events. GLFW reports a change in
+/// the display scale as a framebuffer size change, so the viewport follows it while the
+/// text keeps its size on screen. A minimized window has no size and is ignored. Returns
+/// `true` if the window size changed and the text must be laid out again.
+fn update_window_size(app: &mut App, event: &glfw::WindowEvent) -> bool {
+    match *event {
+        glfw::WindowEvent::Size(width, height) if width > 0 && height > 0 => {
+            let changed = (width as u32 != app.gl.width) || (height as u32 != app.gl.height);
+            app.gl.width = width as u32;
+            app.gl.height = height as u32;
+            changed
+        }
+        glfw::WindowEvent::FramebufferSize(width, height) if width > 0 && height > 0 => {
+            app.framebuffer_width = width as u32;
+            app.framebuffer_height = height as u32;
+            false
+        }
+        _ => false,
+    }
 }
 
 #[derive(Clone, Debug)]
 enum OptError {
+    NoInputFile,
     InputFileDoesNotExist(PathBuf),
+    TextFileDoesNotExist(PathBuf),
 }
 
 impl fmt::Display for OptError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            OptError::NoInputFile => {
+                write!(f, "No font file was given. Pass one with --input.")
+            }
             OptError::InputFileDoesNotExist(ref path) => {
                 write!(f, "The font file {} could not be found.", path.display())
             }
+            OptError::TextFileDoesNotExist(ref path) => {
+                write!(f, "The text file {} could not be found.", path.display())
+            }
         }
     }
 }
@@ -302,17 +673,323 @@ impl std::error::Error for OptError {}
 #[derive(Debug, StructOpt)]
 #[structopt(name = "fontview")]
 #[structopt(about = "A shell utility for view bitmapped font atlas files.")]
+#[structopt(raw(setting = "structopt::clap::AppSettings::AllowNegativeNumbers"))]
 struct Opt {
+    /// The path to the input file. This is required unless a subcommand is given. Besides
+    /// `.bmfa` atlases, AngelCode BMFont `.fnt` files, BMFont JSON fonts and PNG grids of
+    /// glyphs can be viewed.
+    #[structopt(parse(from_os_str))]
+    #[structopt(short = "i", long = "input")]
+    input_path: Option<PathBuf>,
+    /// The size of the cells of a PNG grid font, as `WIDTHxHEIGHT` or a single size.
+    #[structopt(long = "cell-size", parse(try_from_str = "import::parse_cell_size"))]
+    cell_size: Option<(u32, u32)>,
+    /// The code point of the first cell of a PNG grid font.
+    #[structopt(long = "first-code-point", default_value = "32")]
+    first_code_point: usize,
+    /// A GLSL vertex shader to draw the text with instead of the built in one. The
+    /// shader is compiled again whenever the file changes.
+    #[structopt(parse(from_os_str))]
+    #[structopt(long = "vertex-shader")]
+    vertex_shader_path: Option<PathBuf>,
+    /// A GLSL fragment shader to draw the text with instead of the built in ones. The
+    /// shader is compiled again whenever the file changes.
+    #[structopt(parse(from_os_str))]
+    #[structopt(long = "fragment-shader")]
+    fragment_shader_path: Option<PathBuf>,
+    /// Display the contents of a text file instead of the sample text. The text is
+    /// laid out again whenever the file is saved.
+    #[structopt(parse(from_os_str))]
+    #[structopt(long = "text-file")]
+    text_path: Option<PathBuf>,
+    /// Draw a drop shadow behind the text.
+    #[structopt(long = "shadow")]
+    shadow: bool,
+    /// The horizontal offset of the drop shadow in pixels.
+    #[structopt(long = "shadow-offset-x", default_value = "2")]
+    shadow_offset_x: f32,
+    /// The vertical offset of the drop shadow in pixels. Positive values move the shadow down.
+    #[structopt(long = "shadow-offset-y", default_value = "2")]
+    shadow_offset_y: f32,
+    /// The color of the drop shadow, as `#RRGGBB[AA]`, `r,g,b[,a]` or a color name.
+    #[structopt(long = "shadow-color", default_value = "#000000C0")]
+    shadow_color: Color,
+    /// Draw an outline around the text.
+    #[structopt(long = "outline")]
+    outline: bool,
+    /// The width of the outline in pixels.
+    #[structopt(long = "outline-width", default_value = "1")]
+    outline_width: f32,
+    /// The color of the outline, as `#RRGGBB[AA]`, `r,g,b[,a]` or a color name.
+    #[structopt(long = "outline-color", default_value = "#000000")]
+    outline_color: Color,
+    /// Draw a glow around the text. This requires a distance field atlas.
+    #[structopt(long = "glow")]
+    glow: bool,
+    /// The width of the glow in pixels.
+    #[structopt(long = "glow-width", default_value = "6")]
+    glow_width: f32,
+    /// The color of the glow, as `#RRGGBB[AA]`, `r,g,b[,a]` or a color name.
+    #[structopt(long = "glow-color", default_value = "#FFFFFF80")]
+    glow_color: Color,
+    /// Render the atlas as a signed distance field stored in the alpha channel. By default
+    /// the glyph encoding is detected from the atlas image.
+    #[structopt(long = "sdf", conflicts_with = "msdf", conflicts_with = "bitmap")]
+    sdf: bool,
+    /// Render the atlas as a multi-channel signed distance field.
+    #[structopt(long = "msdf", conflicts_with = "bitmap")]
+    msdf: bool,
+    /// Render the atlas as a plain coverage bitmap.
+    #[structopt(long = "bitmap")]
+    bitmap: bool,
+    /// The distance field value at the edge of a glyph.
+    #[structopt(long = "sdf-threshold", default_value = "0.5")]
+    sdf_threshold: f32,
+    /// The width of the antialiased edge of distance field glyphs in pixels.
+    #[structopt(long = "sdf-softness", default_value = "1")]
+    sdf_softness: f32,
+    /// The texture minification filter: nearest, linear, nearest-mipmap-nearest,
+    /// linear-mipmap-nearest, nearest-mipmap-linear or linear-mipmap-linear.
+    #[structopt(long = "min-filter", default_value = "linear-mipmap-linear")]
+    min_filter: MinFilter,
+    /// The texture magnification filter: nearest or linear.
+    #[structopt(long = "mag-filter", default_value = "linear")]
+    mag_filter: MagFilter,
+    /// Do not generate mipmaps for the atlas texture.
+    #[structopt(long = "no-mipmaps")]
+    no_mipmaps: bool,
+    /// The anisotropic filtering level. Defaults to the driver maximum.
+    #[structopt(long = "anisotropy")]
+    anisotropy: Option<f32>,
+    /// The texture wrap mode: clamp-to-edge, clamp-to-border, repeat or mirrored-repeat.
+    #[structopt(long = "wrap", default_value = "clamp-to-edge")]
+    wrap: WrapMode,
+    /// Upload the atlas as an sRGB texture so its colors are decoded to linear space when sampled.
+    #[structopt(long = "srgb-texture")]
+    srgb_texture: bool,
+    /// Render to an sRGB framebuffer so blending happens in linear space. Colors given
+    /// on the command line are treated as sRGB.
+    #[structopt(long = "srgb-framebuffer")]
+    srgb_framebuffer: bool,
+    /// Premultiply the atlas by its alpha channel and blend with premultiplied alpha.
+    #[structopt(long = "premultiplied-alpha")]
+    premultiplied_alpha: bool,
+    /// Start in the atlas inspector, which shows the atlas image with its glyph grid overlaid.
+    #[structopt(long = "atlas-view")]
+    atlas_view: bool,
+    /// Draw the debug overlays for baselines, glyph quads, ink boxes, advances and the wrap margin.
+    #[structopt(long = "debug-overlays")]
+    debug_overlays: bool,
+    /// Where to show the performance statistics: off, title or overlay.
+    #[structopt(long = "hud", default_value = "off")]
+    hud: HudMode,
+    /// Redraw continuously instead of only when something changes, to measure performance.
+    #[structopt(long = "continuous")]
+    continuous: bool,
+    /// Save the first N frames as PNG files next to the font atlas.
+    #[structopt(long = "capture-frames", default_value = "0")]
+    capture_frames: usize,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+/// The subcommands. These work without opening a window, unless `generate` is asked
+/// to show the atlas it writes.
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Print the atlas metadata.
+    #[structopt(name = "info")]
+    Info {
+        /// The path to the input file.
+        #[structopt(parse(from_os_str))]
+        #[structopt(short = "i", long = "input")]
+        input_path: PathBuf,
+        /// Print the metadata as JSON.
+        #[structopt(long = "json")]
+        json: bool,
+    },
+    /// Check an atlas for problems, exiting with an error if any are found.
+    #[structopt(name = "validate")]
+    Validate {
+        /// The path to the input file.
+        #[structopt(parse(from_os_str))]
+        #[structopt(short = "i", long = "input")]
+        input_path: PathBuf,
+        /// A character set the atlas must cover: printable-ascii, latin-1, or a language
+        /// such as french or russian. Can be given more than once.
+        #[structopt(long = "require", default_value = "printable-ascii")]
+        required: Vec<CharacterSet>,
+        /// Allow several code points to use the same glyph cell.
+        #[structopt(long = "allow-shared-cells")]
+        allow_shared_cells: bool,
+    },
+    /// Report how much of each Unicode block and language the atlas covers.
+    #[structopt(name = "coverage")]
+    Coverage {
+        /// The path to the input file.
+        #[structopt(parse(from_os_str))]
+        #[structopt(short = "i", long = "input")]
+        input_path: PathBuf,
+        /// Include the Unicode blocks the atlas has no glyphs in.
+        #[structopt(long = "all-blocks")]
+        all_blocks: bool,
+    },
+    /// Find the strings in text, JSON or PO files that the atlas cannot draw.
+    #[structopt(name = "check")]
+    Check {
+        /// The path to the input file.
+        #[structopt(parse(from_os_str))]
+        #[structopt(short = "i", long = "input")]
+        input_path: PathBuf,
+        /// The files to check. JSON and PO files are recognized by their extension, and
+        /// every line of any other file is checked.
+        #[structopt(parse(from_os_str), raw(required = "true"))]
+        files: Vec<PathBuf>,
+    },
+    /// Lay out a string and write its vertex data, to bake static text into a game.
+    #[structopt(name = "layout")]
+    Layout(LayoutOpt),
+    /// Convert an atlas to another font format.
+    #[structopt(name = "export")]
+    Export {
+        /// The path to the input file.
+        #[structopt(parse(from_os_str))]
+        #[structopt(short = "i", long = "input")]
+        input_path: PathBuf,
+        /// The format to convert to: fnt, fnt-xml or fnt-binary, for the text, XML and
+        /// binary variants of AngelCode BMFont.
+        #[structopt(long = "format", default_value = "fnt")]
+        format: ExportFormat,
+        /// The file to write the font descriptor to. Defaults to the input file with a
+        /// `.fnt` extension. The page image is written next to it.
+        #[structopt(parse(from_os_str))]
+        #[structopt(short = "o", long = "output")]
+        output_path: Option<PathBuf>,
+    },
+    /// Rasterize a TrueType or OpenType font into a `.bmfa` atlas.
+    #[structopt(name = "generate")]
+    Generate(GenerateOpt),
+}
+
+/// The options for the `layout` subcommand.
+#[derive(Debug, StructOpt)]
+struct LayoutOpt {
     /// The path to the input file.
     #[structopt(parse(from_os_str))]
     #[structopt(short = "i", long = "input")]
     input_path: PathBuf,
+    /// The text to lay out.
+    #[structopt(long = "text", required_unless = "text_path", conflicts_with = "text_path")]
+    text: Option<String>,
+    /// Lay out the contents of a text file instead.
+    #[structopt(parse(from_os_str))]
+    #[structopt(long = "text-file")]
+    text_path: Option<PathBuf>,
+    /// The output format: json, binary, rust or c.
+    #[structopt(long = "format", default_value = "json")]
+    format: VertexFormat,
+    /// The name of the arrays in Rust or C source code.
+    #[structopt(long = "name", default_value = "text", parse(try_from_str = "vertex_export::parse_array_name"))]
+    name: String,
+    /// The file to write the vertex data to. Defaults to standard output.
+    #[structopt(parse(from_os_str))]
+    #[structopt(short = "o", long = "output")]
+    output_path: Option<PathBuf>,
+    /// The width of the screen the text is laid out on, in pixels.
+    #[structopt(long = "width", default_value = "1024", parse(try_from_str = "layout::parse_screen_size"))]
+    width: u32,
+    /// The height of the screen the text is laid out on, in pixels.
+    #[structopt(long = "height", default_value = "576", parse(try_from_str = "layout::parse_screen_size"))]
+    height: u32,
+    /// The size of the glyphs in pixels.
+    #[structopt(long = "scale", default_value = "72")]
+    scale_px: f32,
+}
+
+/// The options for the `generate` subcommand.
+#[derive(Debug, StructOpt)]
+struct GenerateOpt {
+    /// The path to the TrueType or OpenType font.
+    #[structopt(parse(from_os_str))]
+    #[structopt(short = "i", long = "input")]
+    input_path: PathBuf,
+    /// The file to write the atlas to. Defaults to the input file with a `.bmfa` extension.
+    #[structopt(parse(from_os_str))]
+    #[structopt(short = "o", long = "output")]
+    output_path: Option<PathBuf>,
+    /// The width and height of each glyph's cell in pixels, padding included.
+    #[structopt(long = "slot-size", default_value = "64")]
+    slot_glyph_size: usize,
+    /// The width in pixels of the empty band along the right and bottom of each cell.
+    #[structopt(long = "padding", default_value = "3")]
+    padding: usize,
+    /// The code points to rasterize, as a comma separated list of code points such as
+    /// U+00E9, ranges such as 32-126, and character sets such as latin-1 or russian.
+    #[structopt(long = "code-points", default_value = "printable-ascii")]
+    code_points: CodePoints,
+    /// The corner texture coordinates are measured from: bottom-left or top-left.
+    #[structopt(long = "origin", default_value = "bottom-left")]
+    origin: AtlasOrigin,
+    /// Open the atlas in the viewer once it is written.
+    #[structopt(long = "view")]
+    view: bool,
+}
+
+impl GenerateOpt {
+    fn output_path(&self) -> PathBuf {
+        self.output_path.clone().unwrap_or_else(|| self.input_path.with_extension("bmfa"))
+    }
+}
+
+impl Command {
+    /// The font atlas the subcommand works on, or the font `generate` rasterizes.
+    fn input_path(&self) -> &Path {
+        match *self {
+            Command::Info { ref input_path, .. } => input_path,
+            Command::Validate { ref input_path, .. } => input_path,
+            Command::Coverage { ref input_path, .. } => input_path,
+            Command::Check { ref input_path, .. } => input_path,
+            Command::Layout(ref layout_opt) => &layout_opt.input_path,
+            Command::Export { ref input_path, .. } => input_path,
+            Command::Generate(ref generate_opt) => &generate_opt.input_path,
+        }
+    }
+}
+
+/// Verify that a font atlas file exists.
+fn verify_input_path(input_path: &Path) -> Result<(), OptError> {
+    if !(input_path.exists() && input_path.is_file()) {
+        return Err(OptError::InputFileDoesNotExist(input_path.to_path_buf()));
+    }
+
+    Ok(())
 }
 
 /// Verify the input options.
 fn verify_opt(opt: &Opt) -> Result<(), OptError> {
-    if !(opt.input_path.exists() && opt.input_path.is_file()) {
-        return Err(OptError::InputFileDoesNotExist(opt.input_path.clone()));
+    if let Some(ref command) = opt.command {
+        if let Command::Check { ref files, .. } = *command {
+            for path in files.iter() {
+                if !(path.exists() && path.is_file()) {
+                    return Err(OptError::TextFileDoesNotExist(path.clone()));
+                }
+            }
+        }
+        if let Command::Layout(LayoutOpt { text_path: Some(ref text_path), .. }) = *command {
+            if !(text_path.exists() && text_path.is_file()) {
+                return Err(OptError::TextFileDoesNotExist(text_path.clone()));
+            }
+        }
+        return verify_input_path(command.input_path());
+    }
+    match opt.input_path {
+        Some(ref input_path) => verify_input_path(input_path)?,
+        None => return Err(OptError::NoInputFile),
+    }
+    if let Some(ref text_path) = opt.text_path {
+        if !(text_path.exists() && text_path.is_file()) {
+            return Err(OptError::TextFileDoesNotExist(text_path.clone()));
+        }
     }
 
     Ok(())
@@ -328,13 +1005,26 @@ fn init_app() -> App {
         }
     };
     let writer = create_text_writer();
-    
-    App { gl: gl_state, writer: writer }
+    let (framebuffer_width, framebuffer_height) = gl_state.window.get_framebuffer_size();
+
+    App {
+        gl: gl_state,
+        writer: writer,
+        framebuffer_width: framebuffer_width as u32,
+        framebuffer_height: framebuffer_height as u32,
+    }
 }
 
 #[derive(Debug)]
 enum AppError {
     CouldNotLoadFontAtlas(Box<dyn std::error::Error>),
+    CouldNotReadTextFile(PathBuf, io::Error),
+    MissingGlyphs(Vec<char>),
+    CouldNotWriteOutput(io::Error),
+    NothingToLayOut,
+    CouldNotCompileShaders(glh::ShaderCompilationError),
+    CouldNotUploadFontAtlas(String),
+    InvalidFontAtlas(PathBuf, usize),
 }
 
 impl fmt::Display for AppError {
@@ -343,96 +1033,1196 @@ impl fmt::Display for AppError {
             AppError::CouldNotLoadFontAtlas(ref e) => {
                 write!(f, "Could not load font atlas. Got error: {}", e)
             }
+            AppError::CouldNotReadTextFile(ref path, ref e) => {
+                write!(f, "Could not read the text file {}. Got error: {}", path.display(), e)
+            }
+            AppError::MissingGlyphs(ref missing) => {
+                let missing: Vec<String> = missing.iter().map(|&ch| format!("U+{:04X}", ch as u32)).collect();
+                write!(f, "The font atlas has no glyphs for {}.", missing.join(", "))
+            }
+            AppError::CouldNotWriteOutput(ref e) => {
+                write!(f, "Could not write the output. Got error: {}", e)
+            }
+            AppError::NothingToLayOut => {
+                write!(f, "The text has no glyphs to lay out.")
+            }
+            AppError::CouldNotCompileShaders(ref e) => {
+                write!(f, "Could not compile the built in shaders. Got error: {}", e)
+            }
+            AppError::CouldNotUploadFontAtlas(ref e) => {
+                write!(f, "Could not upload the font atlas. Got error: {}", e)
+            }
+            AppError::InvalidFontAtlas(ref path, problems) => {
+                write!(f, "Found {} problems in the font atlas {}.", problems, path.display())
+            }
         }
     }
 }
 
 impl std::error::Error for AppError {}
 
-fn run_app(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
-    // Start GL context with helper libraries.
-    let mut app = init_app();
-
-    let renderer = glh::glubyte_ptr_to_string(unsafe { gl::GetString(gl::RENDERER) });
-    let version = glh::glubyte_ptr_to_string(unsafe { gl::GetString(gl::VERSION) });
-    println!("Renderer: {}", renderer);
-    println!("OpenGL version supported {}", version);
-
-    // Load the font atlas.
-    let atlas = match bmfa::load(opt.input_path) {
-        Ok(val) => val,
-        Err(e) => {
-            return Err(Box::new(AppError::CouldNotLoadFontAtlas(Box::new(e))));
+/// Adjust the text effects from the keyboard so the shadow and outline can be tuned
+/// while looking at the text.
+fn update_text_effects(effects: &mut TextEffects, event: &glfw::WindowEvent) {
+    match *event {
+        glfw::WindowEvent::Key(Key::S, _, Action::Press, _) => {
+            effects.shadow_enabled = !effects.shadow_enabled;
         }
-    };
-
-    let placement = create_text_placement();
-
-    // Load the text onto the GPU.
-    let string = DEFAULT_TEXT;
-    let mut point_count = text_to_screen(&mut app, &atlas, placement, string).unwrap().1;
+        glfw::WindowEvent::Key(Key::O, _, Action::Press, _) => {
+            effects.outline_enabled = !effects.outline_enabled;
+        }
+        glfw::WindowEvent::Key(Key::G, _, Action::Press, _) => {
+            effects.glow_enabled = !effects.glow_enabled;
+        }
+        glfw::WindowEvent::Key(Key::LeftBracket, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::LeftBracket, _, Action::Repeat, _) => {
+            effects.outline_width = f32::max(effects.outline_width - 0.5, 0.0);
+        }
+        glfw::WindowEvent::Key(Key::RightBracket, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::RightBracket, _, Action::Repeat, _) => {
+            effects.outline_width += 0.5;
+        }
+        glfw::WindowEvent::Key(Key::Left, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Left, _, Action::Repeat, _) => {
+            effects.shadow_offset_x -= 1.0;
+        }
+        glfw::WindowEvent::Key(Key::Right, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Right, _, Action::Repeat, _) => {
+            effects.shadow_offset_x += 1.0;
+        }
+        glfw::WindowEvent::Key(Key::Up, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Up, _, Action::Repeat, _) => {
+            effects.shadow_offset_y -= 1.0;
+        }
+        glfw::WindowEvent::Key(Key::Down, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Down, _, Action::Repeat, _) => {
+            effects.shadow_offset_y += 1.0;
+        }
+        _ => {}
+    }
+}
 
-    unsafe {
-        gl::BindVertexArray(app.writer.vao);
-        gl::BindBuffer(gl::ARRAY_BUFFER, app.writer.points_vbo);
-        gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
-        gl::EnableVertexAttribArray(0);
-        gl::BindBuffer(gl::ARRAY_BUFFER, app.writer.texcoords_vbo);
-        gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
-        gl::EnableVertexAttribArray(1);
+/// Adjust the distance field edge from the keyboard.
+fn update_distance_field(sdf: &mut DistanceFieldSettings, event: &glfw::WindowEvent) {
+    match *event {
+        glfw::WindowEvent::Key(Key::Comma, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Comma, _, Action::Repeat, _) => {
+            sdf.threshold = f32::max(sdf.threshold - 0.01, 0.0);
+        }
+        glfw::WindowEvent::Key(Key::Period, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Period, _, Action::Repeat, _) => {
+            sdf.threshold = f32::min(sdf.threshold + 0.01, 1.0);
+        }
+        glfw::WindowEvent::Key(Key::Semicolon, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Semicolon, _, Action::Repeat, _) => {
+            sdf.softness = f32::max(sdf.softness - 0.1, 0.0);
+        }
+        glfw::WindowEvent::Key(Key::Apostrophe, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Apostrophe, _, Action::Repeat, _) => {
+            sdf.softness += 0.1;
+        }
+        _ => {}
     }
+}
 
-    let (sp, sp_text_color_loc) = create_shaders(&mut app);
+/// The texture format for the atlas. Only coverage bitmaps are premultiplied, and
+/// multi-channel distance fields are never sRGB decoded, since their color channels
+/// hold distances rather than colors.
+fn texture_format(pipeline: &ColorPipeline, sdf: &DistanceFieldSettings) -> TextureFormat {
+    TextureFormat {
+        srgb: pipeline.srgb_texture && sdf.encoding != GlyphEncoding::Msdf,
+        premultiplied_alpha: pipeline.premultiplied_alpha && sdf.encoding == GlyphEncoding::Bitmap,
+    }
+}
 
-    let tex = load_font_texture(&atlas, gl::CLAMP_TO_EDGE).unwrap();
+fn import_options_from_opt(opt: &Opt) -> ImportOptions {
+    ImportOptions {
+        cell_size: opt.cell_size,
+        first_code_point: opt.first_code_point,
+    }
+}
 
-    unsafe {
-        gl::CullFace(gl::BACK);
-        gl::FrontFace(gl::CCW);
-        gl::Enable(gl::CULL_FACE);
-        // Partial transparency.
-        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-        gl::ClearColor(0.2, 0.2, 0.6, 1.0);
-        gl::Viewport(0, 0, app.gl.width as i32, app.gl.height as i32);
+fn texture_settings_from_opt(opt: &Opt) -> TextureSettings {
+    TextureSettings {
+        min_filter: opt.min_filter,
+        mag_filter: opt.mag_filter,
+        mipmaps: !opt.no_mipmaps,
+        anisotropy: opt.anisotropy,
+        wrap: opt.wrap,
     }
+}
 
-    // The main rendering loop.
-    while !app.gl.window.should_close() {
-        // Update the text display if the frame buffer size changed.
-        let (width, height) = app.gl.window.get_framebuffer_size();
-        if (width != app.gl.width as i32) && (height != app.gl.height as i32) {
-            glfw_framebuffer_size_callback(&mut app, width as u32, height as u32);
-            point_count = text_to_screen(&mut app, &atlas, placement, string).unwrap().1;
+/// Change the atlas texture sampling from the keyboard. Returns `true` if the
+/// settings changed and need to be applied to the texture.
+fn update_texture_settings(settings: &mut TextureSettings, event: &glfw::WindowEvent) -> bool {
+    match *event {
+        glfw::WindowEvent::Key(Key::F, _, Action::Press, _) => {
+            settings.mag_filter = settings.mag_filter.next();
         }
+        glfw::WindowEvent::Key(Key::N, _, Action::Press, _) => {
+            settings.min_filter = settings.min_filter.next();
+        }
+        glfw::WindowEvent::Key(Key::M, _, Action::Press, _) => {
+            settings.mipmaps = !settings.mipmaps;
+        }
+        glfw::WindowEvent::Key(Key::A, _, Action::Press, _) => {
+            settings.next_anisotropy();
+        }
+        glfw::WindowEvent::Key(Key::W, _, Action::Press, _) => {
+            settings.wrap = settings.wrap.next();
+        }
+        _ => return false,
+    }
 
-        unsafe {
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            gl::ClearColor(0.2, 0.2, 0.6, 1.0);
-            gl::Viewport(0, 0, app.gl.width as i32, app.gl.height as i32);
-
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, tex);
-            gl::UseProgram(sp);
-
-            // Draw text with no depth test and alpha blending.
-            gl::Disable(gl::DEPTH_TEST);
-            gl::Enable(gl::BLEND);
+    true
+}
 
-            gl::BindVertexArray(app.writer.vao);
-            gl::Uniform4f(sp_text_color_loc, 1.0, 1.0, 0.0, 1.0);
-            gl::DrawArrays(gl::TRIANGLES, 0, point_count as GLint);
+/// Which view the viewer window shows.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ViewMode {
+    /// The sample text rendered with the atlas.
+    Text,
+    /// The atlas image itself with its glyph grid overlaid.
+    Atlas,
+}
+
+/// The GPU buffers and view state for the atlas inspector.
+struct AtlasInspector {
+    writer: GLTextWriter,
+    overlay: lines::GLLineWriter,
+    camera: Camera,
+    controller: CameraController,
+}
+
+fn create_atlas_inspector() -> AtlasInspector {
+    AtlasInspector {
+        writer: create_text_writer(),
+        overlay: lines::create_line_writer(),
+        camera: Camera::new(),
+        controller: CameraController::new(),
+    }
+}
+
+/// Lay out the atlas image and its overlay for the current window size and load them
+/// onto the GPU.
+fn update_atlas_inspector(
+    app: &App, inspector: &mut AtlasInspector,
+    atlas: &bmfa::BitmapFontAtlas, pipeline: &ColorPipeline) -> io::Result<()> {
+
+    let layout = AtlasLayout::new(atlas, app.gl.width, app.gl.height);
+    let (points, texcoords) = layout.quad();
+    inspector.writer.write(&points, &texcoords)?;
+
+    let colors = AtlasOverlayColors::new().map(|color| pipeline.overlay_color(color));
+    let mut batch = LineBatch::new();
+    atlas_view::atlas_overlay(atlas, &layout, &colors, &mut batch);
+    inspector.overlay.write(&batch)?;
+
+    Ok(())
+}
+
+/// Draw the atlas image followed by the glyph grid overlay.
+fn draw_atlas_inspector(
+    inspector: &AtlasInspector, shader: &TextShader,
+    line_shader: &lines::LineShader, pipeline: &ColorPipeline) {
+
+    let color = pipeline.overlay_color(Color::new(1.0, 1.0, 1.0, 1.0));
+    shader.use_with_camera(&inspector.camera);
+    unsafe {
+        gl::Uniform4f(shader.text_color_loc, color.r, color.g, color.b, color.a);
+        gl::Uniform2f(shader.offset_loc, 0.0, 0.0);
+        gl::BindVertexArray(inspector.writer.vao);
+        gl::DrawArrays(gl::TRIANGLES, 0, 6);
+    }
+
+    lines::draw_lines(line_shader, &inspector.overlay, &inspector.camera);
+}
+
+/// The GPU buffers of a panel drawn in screen space over the scene.
+struct ScreenPanel {
+    /// Set when what the panel shows or the window size changed and the panel must be rebuilt.
+    dirty: bool,
+    writer: GLTextWriter,
+    overlay: lines::GLLineWriter,
+    point_count: usize,
+}
+
+fn create_screen_panel() -> ScreenPanel {
+    ScreenPanel {
+        dirty: true,
+        writer: create_text_writer(),
+        overlay: lines::create_line_writer(),
+        point_count: 0,
+    }
+}
+
+impl ScreenPanel {
+    /// Upload a laid out panel to the GPU, replacing the previous one.
+    fn write(&mut self, panel: &Panel) -> io::Result<()> {
+        self.writer.write(&panel.text.points, &panel.text.texcoords)?;
+        self.point_count = panel.text.point_count();
+        self.overlay.write(&panel.overlay)?;
+
+        Ok(())
+    }
+
+    /// Empty the panel, so nothing is drawn until the next one is written.
+    fn clear(&mut self) -> io::Result<()> {
+        self.point_count = 0;
+        self.overlay.write(&LineBatch::new())?;
+
+        Ok(())
+    }
+}
+
+/// The glyph inspector that shows the metadata of the glyph under the cursor.
+struct GlyphHover {
+    enabled: bool,
+    /// The cursor position in normalized device coordinates, if it is in the window.
+    cursor: Option<(f32, f32)>,
+    panel: ScreenPanel,
+}
+
+fn create_glyph_hover() -> GlyphHover {
+    GlyphHover {
+        enabled: true,
+        cursor: None,
+        panel: create_screen_panel(),
+    }
+}
+
+/// Track the cursor for the glyph inspector. The `I` key toggles the inspector.
+fn update_glyph_hover_input(hover: &mut GlyphHover, window: &glfw::Window, event: &glfw::WindowEvent) {
+    match *event {
+        glfw::WindowEvent::CursorPos(cursor_x, cursor_y) => {
+            hover.cursor = Some(camera::cursor_to_ndc(window, cursor_x, cursor_y));
+            hover.panel.dirty = true;
+        }
+        glfw::WindowEvent::CursorEnter(false) => {
+            hover.cursor = None;
+            hover.panel.dirty = true;
+        }
+        glfw::WindowEvent::Key(Key::I, _, Action::Press, _) => {
+            hover.enabled = !hover.enabled;
+            hover.panel.dirty = true;
+        }
+        _ => {}
+    }
+}
+
+/// Rebuild the glyph inspector panel for the glyph under the cursor, if anything changed.
+fn update_glyph_hover(
+    app: &App, hover: &mut GlyphHover, atlas: &bmfa::BitmapFontAtlas,
+    text_layout: &TextLayout, camera: &Camera, pipeline: &ColorPipeline) -> io::Result<()> {
+
+    if !hover.panel.dirty {
+        return Ok(());
+    }
+    hover.panel.dirty = false;
+
+    let hovered = match hover.cursor {
+        Some((x, y)) if hover.enabled => {
+            let (scene_x, scene_y) = camera.to_scene(x, y);
+            text_layout.glyph_at(scene_x, scene_y).map(|glyph| (*glyph, (x, y)))
+        }
+        _ => None,
+    };
+
+    match hovered {
+        Some((glyph, cursor)) => {
+            // The hovered glyph is highlighted on screen, so apply the view transform to its box.
+            let glyph_box = (
+                glyph.left * camera.zoom + camera.pan_x,
+                glyph.top * camera.zoom + camera.pan_y,
+                (glyph.left + glyph.advance) * camera.zoom + camera.pan_x,
+                glyph.bottom * camera.zoom + camera.pan_y,
+            );
+            let panel = hover::hover_panel(
+                atlas, &glyph, glyph_box, cursor, app.gl.width, app.gl.height,
+                pipeline.overlay_color(Color::new(0.0, 0.0, 0.0, 0.75)),
+                pipeline.overlay_color(Color::new(1.0, 1.0, 1.0, 0.9)),
+            );
+            hover.panel.write(&panel)?;
+        }
+        None => {
+            hover.panel.clear()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw a panel of white text over its background. Panels are drawn in screen space
+/// without any text effects, so they stay put and readable while the view changes.
+fn draw_screen_panel(
+    app: &App, panel: &ScreenPanel, shaders: &TextShaders, line_shader: &lines::LineShader,
+    sdf: &DistanceFieldSettings, pipeline: &ColorPipeline, effects: &TextEffects) {
+
+    if panel.point_count == 0 {
+        return;
+    }
+
+    let screen = Camera::new();
+    let plain = TextEffects {
+        shadow_enabled: false,
+        outline_enabled: false,
+        glow_enabled: false,
+        ..*effects
+    };
+    lines::draw_lines(line_shader, &panel.overlay, &screen);
+    draw_text(
+        app, &panel.writer, shaders, &screen, &plain, sdf, pipeline,
+        Color::new(1.0, 1.0, 1.0, 1.0), panel.point_count
+    );
+}
+
+/// The debug overlays drawn over the text in the text view.
+struct TextDebugOverlay {
+    overlays: DebugOverlays,
+    /// Set when the text or the enabled overlays changed and the overlay must be rebuilt.
+    dirty: bool,
+    writer: lines::GLLineWriter,
+}
+
+fn create_text_debug_overlay(enabled: bool) -> TextDebugOverlay {
+    TextDebugOverlay {
+        overlays: DebugOverlays::new(enabled),
+        dirty: true,
+        writer: lines::create_line_writer(),
+    }
+}
+
+/// Toggle the debug overlays. The `1` key toggles the baselines, `2` the glyph quads,
+/// `3` the ink boxes, `4` the advances, and `5` the wrap margin. The `D` key turns them
+/// all off if any are on, and all on otherwise.
+fn update_text_debug_overlay_input(debug: &mut TextDebugOverlay, event: &glfw::WindowEvent) {
+    if let glfw::WindowEvent::Key(Key::D, _, Action::Press, _) = *event {
+        debug.overlays = DebugOverlays::new(!debug.overlays.any());
+        debug.dirty = true;
+        return;
+    }
+
+    let overlays = &mut debug.overlays;
+    let toggled = match *event {
+        glfw::WindowEvent::Key(Key::Num1, _, Action::Press, _) => &mut overlays.baselines,
+        glfw::WindowEvent::Key(Key::Num2, _, Action::Press, _) => &mut overlays.quads,
+        glfw::WindowEvent::Key(Key::Num3, _, Action::Press, _) => &mut overlays.ink_boxes,
+        glfw::WindowEvent::Key(Key::Num4, _, Action::Press, _) => &mut overlays.advances,
+        glfw::WindowEvent::Key(Key::Num5, _, Action::Press, _) => &mut overlays.wrap_margin,
+        _ => return,
+    };
+    *toggled = !*toggled;
+    debug.dirty = true;
+}
+
+/// Rebuild the debug overlays for the laid out text, if anything changed.
+fn update_text_debug_overlay(
+    app: &App, debug: &mut TextDebugOverlay, atlas: &bmfa::BitmapFontAtlas,
+    text_layout: &TextLayout, placement: &TextPlacement, pipeline: &ColorPipeline) -> io::Result<()> {
+
+    if !debug.dirty {
+        return Ok(());
+    }
+    debug.dirty = false;
+
+    let colors = DebugOverlayColors::new().map(|color| pipeline.overlay_color(color));
+    let mut batch = LineBatch::new();
+    debug_overlay::text_overlay(
+        atlas, text_layout, placement, app.gl.width, app.gl.height,
+        &debug.overlays, &colors, &mut batch
+    );
+    debug.writer.write(&batch)?;
+
+    Ok(())
+}
+
+/// The smallest and largest text sizes the keyboard can set, and the step between sizes.
+const MIN_SCALE_PX: f32 = 8.0;
+const MAX_SCALE_PX: f32 = 512.0;
+const SCALE_PX_STEP: f32 = 8.0;
+
+/// The text size and colors, as set from the keyboard.
+#[derive(Copy, Clone, Debug)]
+struct DisplaySettings {
+    scale_px: f32,
+    text_color: usize,
+    background_color: usize,
+}
+
+impl DisplaySettings {
+    fn new(scale_px: f32) -> DisplaySettings {
+        DisplaySettings {
+            scale_px: scale_px,
+            text_color: 0,
+            background_color: 0,
+        }
+    }
+
+    fn text_color(&self) -> Color {
+        color::TEXT_COLOR_PRESETS[self.text_color]
+    }
+
+    fn background_color(&self) -> Color {
+        color::BACKGROUND_COLOR_PRESETS[self.background_color]
+    }
+}
+
+/// Change the text size and colors. The `=` and `-` keys grow and shrink the text,
+/// `C` cycles the text color, and `B` cycles the background color. Returns `true`
+/// if the text size changed and the text must be laid out again.
+fn update_display_settings(display: &mut DisplaySettings, event: &glfw::WindowEvent) -> bool {
+    match *event {
+        glfw::WindowEvent::Key(Key::Equal, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Equal, _, Action::Repeat, _) |
+        glfw::WindowEvent::Key(Key::KpAdd, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::KpAdd, _, Action::Repeat, _) => {
+            display.scale_px = f32::min(display.scale_px + SCALE_PX_STEP, MAX_SCALE_PX);
+            true
+        }
+        glfw::WindowEvent::Key(Key::Minus, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::Minus, _, Action::Repeat, _) |
+        glfw::WindowEvent::Key(Key::KpSubtract, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::KpSubtract, _, Action::Repeat, _) => {
+            display.scale_px = f32::max(display.scale_px - SCALE_PX_STEP, MIN_SCALE_PX);
+            true
+        }
+        glfw::WindowEvent::Key(Key::C, _, Action::Press, _) => {
+            display.text_color = (display.text_color + 1) % color::TEXT_COLOR_PRESETS.len();
+            false
+        }
+        glfw::WindowEvent::Key(Key::B, _, Action::Press, _) => {
+            display.background_color = (display.background_color + 1) % color::BACKGROUND_COLOR_PRESETS.len();
+            false
+        }
+        _ => false,
+    }
+}
+
+/// The on screen help panel listing the key bindings, along with the settings they change.
+struct HelpOverlay {
+    visible: bool,
+    /// The settings shown under the key bindings.
+    status: String,
+    panel: ScreenPanel,
+}
+
+fn create_help_overlay() -> HelpOverlay {
+    HelpOverlay {
+        visible: false,
+        status: String::new(),
+        panel: create_screen_panel(),
+    }
+}
+
+/// Describe the settings the hotkeys change, one per line, for the help panel.
+fn help_status(display: &DisplaySettings, debug: &TextDebugOverlay, hud: &PerformanceHud) -> String {
+    format!(
+        "Text size         {} px\nDebug overlays    {}\nPerformance       {}",
+        display.scale_px, debug.overlays, hud.mode
+    )
+}
+
+/// The `H` and `F1` keys show and hide the help panel.
+fn update_help_overlay_input(help: &mut HelpOverlay, event: &glfw::WindowEvent) {
+    match *event {
+        glfw::WindowEvent::Key(Key::H, _, Action::Press, _) |
+        glfw::WindowEvent::Key(Key::F1, _, Action::Press, _) => {
+            help.visible = !help.visible;
+        }
+        _ => {}
+    }
+}
+
+/// Rebuild the help panel for the current settings and window size, if they changed.
+fn update_help_overlay(
+    app: &App, help: &mut HelpOverlay, status: String,
+    atlas: &bmfa::BitmapFontAtlas, pipeline: &ColorPipeline) -> io::Result<()> {
+
+    if status != help.status {
+        help.status = status;
+        help.panel.dirty = true;
+    }
+    if !help.panel.dirty {
+        return Ok(());
+    }
+    help.panel.dirty = false;
+
+    let background = pipeline.overlay_color(Color::new(0.0, 0.0, 0.0, 0.75));
+    let panel = help::help_panel(atlas, &help.status, app.gl.width, app.gl.height, background);
+    help.panel.write(&panel)
+}
+
+/// The caret drawn in the text while it is being edited.
+struct CaretOverlay {
+    /// Set when the text or the caret moved and the caret must be rebuilt.
+    dirty: bool,
+    writer: lines::GLLineWriter,
+}
+
+fn create_caret_overlay() -> CaretOverlay {
+    CaretOverlay {
+        dirty: true,
+        writer: lines::create_line_writer(),
+    }
+}
+
+/// Rebuild the caret for the laid out text, if anything changed.
+fn update_caret_overlay(
+    app: &App, caret: &mut CaretOverlay, editor: &TextEditor, text_layout: &TextLayout,
+    placement: &TextPlacement, color: Color) -> io::Result<()> {
+
+    if !caret.dirty {
+        return Ok(());
+    }
+    caret.dirty = false;
+
+    let mut batch = LineBatch::new();
+    editor::caret(editor, text_layout, placement, app.gl.width, app.gl.height, color, &mut batch);
+    caret.writer.write(&batch)?;
+
+    Ok(())
+}
+
+/// The error banner shown across the bottom of the window while something the viewer
+/// reloads from disk is broken.
+struct ErrorBanner {
+    message: Option<String>,
+    panel: ScreenPanel,
+}
+
+fn create_error_banner() -> ErrorBanner {
+    ErrorBanner {
+        message: None,
+        panel: create_screen_panel(),
+    }
+}
+
+/// Show an error message in the banner, or hide the banner.
+fn set_error_banner(error: &mut ErrorBanner, message: Option<String>) {
+    if let Some(ref message) = message {
+        eprintln!("{}", message);
+    }
+    error.message = message;
+    error.panel.dirty = true;
+}
+
+/// Rebuild the error banner for its message and the current window size, if they changed.
+fn update_error_banner(
+    app: &App, error: &mut ErrorBanner,
+    atlas: &bmfa::BitmapFontAtlas, pipeline: &ColorPipeline) -> io::Result<()> {
+
+    if !error.panel.dirty {
+        return Ok(());
+    }
+    error.panel.dirty = false;
+
+    match error.message {
+        Some(ref message) => {
+            let background = pipeline.overlay_color(Color::new(0.6, 0.0, 0.0, 0.9));
+            let panel = banner::banner(atlas, message, app.gl.width, app.gl.height, background);
+            error.panel.write(&panel)
+        }
+        None => error.panel.clear(),
+    }
+}
+
+/// Load the font atlas again after it changed on disk, and upload it to a new texture.
+/// The new atlas must have a glyph for every character of the text on screen, so the
+/// text can still be drawn with it.
+fn reload_atlas(
+    opt: &Opt, input_path: &Path, text: &str, sdf: &DistanceFieldSettings, pipeline: &ColorPipeline,
+    settings: &TextureSettings) -> Result<(bmfa::BitmapFontAtlas, GlyphEncoding, GLuint), String> {
+
+    let atlas = import::load(input_path, &import_options_from_opt(opt)).map_err(|e| {
+        format!("Could not reload the font atlas {}. Got error: {}", input_path.display(), e)
+    })?;
+    if let Some(&(_, ch)) = layout::missing_glyphs(&atlas, text).first() {
+        return Err(format!(
+            "Could not reload the font atlas {}. It has no glyph for U+{:04X} in the text.",
+            input_path.display(), ch as u32
+        ));
+    }
+
+    let encoding = DistanceFieldSettings::from_opt(opt, &atlas).encoding;
+    let format = texture_format(pipeline, &DistanceFieldSettings { encoding: encoding, ..*sdf });
+    let tex = texture::load_font_texture(&atlas, &format, settings).map_err(|e| {
+        format!("Could not upload the font atlas {}. Got error: {}", input_path.display(), e)
+    })?;
+
+    Ok((atlas, encoding, tex))
+}
+
+/// Read the text to display from a file. Characters the atlas has no glyph for are
+/// replaced so the text can be drawn with the atlas. Returns the text along with a
+/// warning if any characters were replaced.
+fn load_text_file(path: &Path, atlas: &bmfa::BitmapFontAtlas) -> io::Result<(String, Option<String>)> {
+    let text = fs::read_to_string(path)?.replace("\r\n", "\n");
+    let missing = layout::missing_glyphs(atlas, &text).len();
+    let warning = if missing > 0 {
+        Some(format!(
+            "{} characters in the text file {} have no glyph in the font atlas.",
+            missing, path.display()
+        ))
+    } else {
+        None
+    };
+
+    Ok((text_help::printable_text(atlas, &text), warning))
+}
+
+/// Save the frame in the back buffer as a PNG file next to the font atlas.
+fn take_screenshot(app: &App, atlas_path: &Path, label: &str) {
+    let path = screenshot::screenshot_path(atlas_path, label);
+    match screenshot::save_screenshot(&path, app.framebuffer_width, app.framebuffer_height) {
+        Ok(_) => println!("Saved screenshot {}", path.display()),
+        Err(e) => eprintln!("Could not save screenshot {}. Got error: {}", path.display(), e),
+    }
+}
+
+/// The performance statistics display.
+struct PerformanceHud {
+    mode: HudMode,
+    stats: FrameStats,
+    panel: ScreenPanel,
+}
+
+fn create_performance_hud(mode: HudMode) -> PerformanceHud {
+    PerformanceHud {
+        mode: mode,
+        stats: FrameStats::new(),
+        panel: create_screen_panel(),
+    }
+}
+
+/// The `P` key cycles the performance statistics between off, the title bar and the overlay.
+fn update_performance_hud_input(app: &mut App, hud: &mut PerformanceHud, event: &glfw::WindowEvent) {
+    if let glfw::WindowEvent::Key(Key::P, _, Action::Press, _) = *event {
+        if hud.mode == HudMode::Title {
+            app.gl.window.set_title(glh::WINDOW_TITLE);
+        }
+        hud.mode = hud.mode.next();
+        hud.panel.dirty = true;
+    }
+}
+
+/// Record how long the frame just drawn took, from the start of drawing it until after
+/// its buffers were swapped, and show the statistics in the title bar when they are updated.
+/// The GL timers are reset at the start of each frame, so the time since then is the frame time.
+fn record_frame_time(app: &mut App, hud: &mut PerformanceHud) {
+    let frame_seconds = glh::update_timers(&mut app.gl);
+    if hud.stats.update(app.gl.running_time_seconds, frame_seconds) {
+        hud.panel.dirty = true;
+        if hud.mode == HudMode::Title {
+            app.gl.window.set_title(&format!("{} @ {}", glh::WINDOW_TITLE, hud.stats));
+        }
+    }
+}
+
+/// Lay out the performance overlay again whenever the statistics are updated.
+fn update_performance_hud(
+    app: &mut App, hud: &mut PerformanceHud, atlas: &bmfa::BitmapFontAtlas,
+    text_layout: &TextLayout, pipeline: &ColorPipeline) -> io::Result<()> {
+
+    hud.stats.set_text(text_layout);
+    if hud.mode == HudMode::Overlay && hud.panel.dirty {
+        hud.panel.dirty = false;
+        let background = pipeline.overlay_color(Color::new(0.0, 0.0, 0.0, 0.75));
+        let panel = perf::hud_panel(atlas, &hud.stats, app.gl.width, app.gl.height, background);
+        hud.panel.write(&panel)?;
+    }
+
+    Ok(())
+}
+
+/// Load a font atlas from disk, converting it from another font format if need be.
+fn load_atlas(input_path: &Path, import_options: &ImportOptions) -> Result<bmfa::BitmapFontAtlas, AppError> {
+    import::load(input_path, import_options).map_err(AppError::CouldNotLoadFontAtlas)
+}
+
+/// Print the metadata of a font atlas, for the `info` subcommand.
+fn run_info(input_path: &Path, import_options: &ImportOptions, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let atlas = load_atlas(input_path, import_options)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info::describe_json(input_path, &atlas))?);
+    } else {
+        println!("{}", info::describe(input_path, &atlas));
+    }
+
+    Ok(())
+}
+
+/// Check a font atlas for problems, for the `validate` subcommand. Exits with an
+/// error code if any are found, so the check can fail a build.
+fn run_validate(
+    input_path: &Path, import_options: &ImportOptions,
+    options: &validate::ValidationOptions) -> Result<(), Box<dyn std::error::Error>> {
+
+    let atlas = load_atlas(input_path, import_options)?;
+
+    if let Err(e) = report_problems(input_path, &validate::validate(&atlas, options)) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+    println!("The font atlas {} is valid.", input_path.display());
+
+    Ok(())
+}
+
+/// Print the problems found in a font atlas, one per line. Fails if there are any.
+fn report_problems(input_path: &Path, problems: &[validate::Problem]) -> Result<(), AppError> {
+    for problem in problems.iter() {
+        println!("{}: {}", input_path.display(), problem);
+    }
+    if !problems.is_empty() {
+        return Err(AppError::InvalidFontAtlas(input_path.to_path_buf(), problems.len()));
+    }
+
+    Ok(())
+}
+
+/// Check text files for strings the font atlas cannot draw, for the `check` subcommand.
+/// Exits with an error code if any are found.
+fn run_check(input_path: &Path, import_options: &ImportOptions, files: &[PathBuf]) -> Result<(), Box<dyn std::error::Error>> {
+    let atlas = load_atlas(input_path, import_options)?;
+
+    let mut unrenderable = Vec::new();
+    for path in files.iter() {
+        let strings = check::check_file(&atlas, path).map_err(|e| AppError::CouldNotReadTextFile(path.clone(), e))?;
+        for string in strings.iter() {
+            println!("{}", string);
+        }
+        unrenderable.extend(strings);
+    }
+    if !unrenderable.is_empty() {
+        eprintln!("{}", check::summary(&unrenderable));
+        process::exit(1);
+    }
+    println!("The font atlas {} can draw every string in {} files.", input_path.display(), files.len());
+
+    Ok(())
+}
+
+/// Lay out a string and write its vertex data, for the `layout` subcommand. The text
+/// is laid out exactly as the viewer lays it out on a screen of the same size.
+fn run_layout(opt: &LayoutOpt, import_options: &ImportOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let atlas = load_atlas(&opt.input_path, import_options)?;
+    let text = match opt.text_path {
+        Some(ref text_path) => {
+            let text = fs::read_to_string(text_path).map_err(|e| AppError::CouldNotReadTextFile(text_path.clone(), e))?;
+            text.replace("\r\n", "\n")
+        }
+        None => opt.text.clone().unwrap_or_default(),
+    };
+    let mut missing: Vec<char> = layout::missing_glyphs(&atlas, &text).into_iter().map(|(_, ch)| ch).collect();
+    if !missing.is_empty() {
+        missing.sort();
+        missing.dedup();
+        return Err(Box::new(AppError::MissingGlyphs(missing)));
+    }
+
+    let placement = TextPlacement { scale_px: opt.scale_px, ..create_text_placement() };
+    let text_layout = layout::layout_text(&atlas, placement, opt.width, opt.height, &text);
+    let data = vertex_export::vertex_data(&text_layout);
+    if data.indices.is_empty() {
+        return Err(Box::new(AppError::NothingToLayOut));
+    }
+    let result = match opt.output_path {
+        Some(ref output_path) => {
+            let mut writer = io::BufWriter::new(fs::File::create(output_path).map_err(AppError::CouldNotWriteOutput)?);
+            vertex_export::write_vertex_data(&data, opt.format, &opt.name, &mut writer)
+                .and_then(|_| io::Write::flush(&mut writer))
+        }
+        None => {
+            let stdout = io::stdout();
+            let mut writer = stdout.lock();
+            vertex_export::write_vertex_data(&data, opt.format, &opt.name, &mut writer)
+        }
+    };
+    result.map_err(AppError::CouldNotWriteOutput)?;
+
+    Ok(())
+}
+
+/// Convert a font atlas to an AngelCode BMFont descriptor and page image, for the
+/// `export` subcommand.
+fn run_export(
+    input_path: &Path, import_options: &ImportOptions,
+    format: ExportFormat, output_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+
+    let atlas = load_atlas(input_path, import_options)?;
+    let (fnt_path, page_path) = fnt::export_paths(output_path);
+    let face = input_path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+    let page = page_path.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+    let font = fnt::fnt_from_atlas(&atlas, &face, &page);
+
+    let mut writer = io::BufWriter::new(fs::File::create(&fnt_path).map_err(AppError::CouldNotWriteOutput)?);
+    fnt::write_fnt(&font, format, &mut writer)
+        .and_then(|_| io::Write::flush(&mut writer))
+        .map_err(AppError::CouldNotWriteOutput)?;
+    image::save_buffer(&page_path, &fnt::page_image(&atlas), atlas.width as u32, atlas.height as u32, image::RGBA(8))
+        .map_err(AppError::CouldNotWriteOutput)?;
+    println!("Wrote {} and {}", fnt_path.display(), page_path.display());
+
+    Ok(())
+}
+
+/// Rasterize a TrueType or OpenType font into a `.bmfa` atlas, for the `generate`
+/// subcommand.
+fn run_generate(generate_opt: &GenerateOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let options = generate::GenerateOptions {
+        slot_glyph_size: generate_opt.slot_glyph_size,
+        padding: generate_opt.padding,
+        code_points: generate_opt.code_points.0.clone(),
+        origin: generate_opt.origin,
+    };
+    let atlas = generate::generate(&generate_opt.input_path, &options)?;
+    if !atlas.missing.is_empty() {
+        let missing: Vec<String> = atlas.missing.iter().map(|code_point| format!("U+{:04X}", code_point)).collect();
+        eprintln!("The font has no glyphs for {}.", missing.join(", "));
+    }
+
+    let output_path = generate_opt.output_path();
+    generate::write_bmfa(&atlas, &output_path).map_err(AppError::CouldNotWriteOutput)?;
+    println!(
+        "Wrote {} glyphs in a {}x{} grid to {}",
+        atlas.glyphs.len(), atlas.columns, atlas.rows, output_path.display()
+    );
+
+    Ok(())
+}
+
+/// How long to wait for window events before checking the watched files again, in seconds.
+const WAIT_EVENTS_TIMEOUT_SECONDS: f64 = 0.5;
+
+fn run_app(opt: Opt, input_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Start GL context with helper libraries.
+    let mut app = init_app();
+
+    let renderer = glh::glubyte_ptr_to_string(unsafe { gl::GetString(gl::RENDERER) });
+    let version = glh::glubyte_ptr_to_string(unsafe { gl::GetString(gl::VERSION) });
+    println!("Renderer: {}", renderer);
+    println!("OpenGL version supported {}", version);
+
+    // Load the font atlas.
+    let mut atlas = load_atlas(input_path, &import_options_from_opt(&opt))?;
+
+    let mut placement = create_text_placement();
+    let mut display = DisplaySettings::new(placement.scale_px);
+
+    // Load the text onto the GPU.
+    let (text, text_warning) = match opt.text_path {
+        Some(ref text_path) => load_text_file(text_path, &atlas)?,
+        None => (String::from(DEFAULT_TEXT), None),
+    };
+    let mut editor = TextEditor::new(&text);
+    let mut text_layout = text_to_screen(&mut app, &atlas, placement, &editor.text)?;
+
+    // Fall back to the built in shaders if the custom ones are broken, and show why.
+    let shader_paths = ShaderPaths::from_opt(&opt);
+    let (mut shaders, shader_error) = match create_shaders(&app, &shader_paths) {
+        Ok(val) => (val, None),
+        Err(e) => {
+            let shaders = create_shaders(&app, &ShaderPaths::default()).map_err(AppError::CouldNotCompileShaders)?;
+            (shaders, Some(format!("Could not compile the custom shaders. Got error: {}", e)))
         }
+    };
+    let mut effects = TextEffects::from_opt(&opt);
+    let mut sdf = DistanceFieldSettings::from_opt(&opt, &atlas);
+    println!("Glyph encoding: {}", sdf.encoding);
+
+    let mut texture_settings = texture_settings_from_opt(&opt);
+    let pipeline = ColorPipeline::from_opt(&opt);
+    let format = texture_format(&pipeline, &sdf);
+    let mut tex = texture::load_font_texture(&atlas, &format, &texture_settings)
+        .map_err(AppError::CouldNotUploadFontAtlas)?;
+    println!("Texture: {}", texture_settings);
+
+    let clear_color = pipeline.shader_color(display.background_color());
+    unsafe {
+        gl::CullFace(gl::BACK);
+        gl::FrontFace(gl::CCW);
+        gl::Enable(gl::CULL_FACE);
+        gl::ClearColor(clear_color.r, clear_color.g, clear_color.b, clear_color.a);
+        gl::Viewport(0, 0, app.framebuffer_width as i32, app.framebuffer_height as i32);
+    }
+    // Partial transparency.
+    pipeline.enable();
+
+    let mut text_camera = Camera::new();
+    let mut text_controller = CameraController::new();
+    let mut view_mode = if opt.atlas_view { ViewMode::Atlas } else { ViewMode::Text };
+    let line_shader = lines::create_line_shader(&app.gl);
+    let mut inspector = create_atlas_inspector();
+    update_atlas_inspector(&app, &mut inspector, &atlas, &pipeline)?;
+    let mut hover = create_glyph_hover();
+    let mut debug = create_text_debug_overlay(opt.debug_overlays);
+    let mut help = create_help_overlay();
+    let mut caret = create_caret_overlay();
+    let mut error = create_error_banner();
+    let mut atlas_watcher = FileWatcher::new(input_path);
+    let mut text_watcher = opt.text_path.as_ref().map(FileWatcher::new);
+    if text_warning.is_some() {
+        set_error_banner(&mut error, text_warning);
+    }
+    if shader_error.is_some() {
+        set_error_banner(&mut error, shader_error);
+    }
+    let mut shader_watchers: Vec<FileWatcher> = shader_paths.vertex.iter()
+        .chain(shader_paths.fragment.iter())
+        .map(FileWatcher::new)
+        .collect();
+
+    let mut hud = create_performance_hud(opt.hud);
+    let mut screenshot_requested = false;
+    let mut resized = false;
+    let mut needs_redraw = true;
+    let mut frame = 0;
 
-        app.gl.glfw.poll_events();
-        match app.gl.window.get_key(Key::Escape) {
-            Action::Press | Action::Repeat => {
-                app.gl.window.set_should_close(true);
+    // The main rendering loop.
+    while !app.gl.window.should_close() {
+        // Reload the atlas if it changed on disk, keeping the old one if the new one is broken.
+        if atlas_watcher.poll() {
+            needs_redraw = true;
+            match reload_atlas(&opt, input_path, &editor.text, &sdf, &pipeline, &texture_settings) {
+                Ok((new_atlas, encoding, new_tex)) => {
+                    unsafe {
+                        gl::DeleteTextures(1, &tex);
+                    }
+                    atlas = new_atlas;
+                    tex = new_tex;
+                    if encoding != sdf.encoding {
+                        sdf.encoding = encoding;
+                        println!("Glyph encoding: {}", sdf.encoding);
+                    }
+                    text_layout = text_to_screen(&mut app, &atlas, placement, &editor.text)?;
+                    update_atlas_inspector(&app, &mut inspector, &atlas, &pipeline)?;
+                    hover.panel.dirty = true;
+                    debug.dirty = true;
+                    help.panel.dirty = true;
+                    caret.dirty = true;
+                    set_error_banner(&mut error, None);
+                    println!("Reloaded font atlas {}", atlas_watcher.path().display());
+                }
+                Err(message) => {
+                    set_error_banner(&mut error, Some(message));
+                }
+            }
+        }
+
+        // Lay out the text file again if it changed on disk.
+        if text_watcher.as_mut().map_or(false, |watcher| watcher.poll()) {
+            needs_redraw = true;
+            let text_path = text_watcher.as_ref().unwrap().path();
+            match load_text_file(text_path, &atlas) {
+                Ok((text, warning)) => {
+                    editor.set_text(&text);
+                    text_layout = text_to_screen(&mut app, &atlas, placement, &editor.text)?;
+                    hover.panel.dirty = true;
+                    debug.dirty = true;
+                    caret.dirty = true;
+                    set_error_banner(&mut error, warning);
+                    println!("Reloaded text file {}", text_path.display());
+                }
+                Err(e) => {
+                    let message = format!("Could not reload the text file {}. Got error: {}", text_path.display(), e);
+                    set_error_banner(&mut error, Some(message));
+                }
+            }
+        }
+
+        // Compile the custom shaders again if they changed on disk, keeping the last
+        // shaders that compiled if the new ones do not.
+        let mut shaders_changed = false;
+        for watcher in shader_watchers.iter_mut() {
+            shaders_changed |= watcher.poll();
+        }
+        if shaders_changed {
+            needs_redraw = true;
+            match create_shaders(&app, &shader_paths) {
+                Ok(new_shaders) => {
+                    shaders.delete();
+                    shaders = new_shaders;
+                    set_error_banner(&mut error, None);
+                    println!("Reloaded custom shaders");
+                }
+                Err(e) => {
+                    let message = format!("Could not compile the custom shaders. Got error: {}", e);
+                    set_error_banner(&mut error, Some(message));
+                }
             }
-            _ => {}
         }
 
-        // Send the results to the output.
-        app.gl.window.swap_buffers();
+        // Update the text display if the window size changed.
+        if resized {
+            resized = false;
+            text_layout = text_to_screen(&mut app, &atlas, placement, &editor.text)?;
+            update_atlas_inspector(&app, &mut inspector, &atlas, &pipeline)?;
+            hover.panel.dirty = true;
+            debug.dirty = true;
+            help.panel.dirty = true;
+            caret.dirty = true;
+            error.panel.dirty = true;
+            hud.panel.dirty = true;
+        }
+
+        // Only draw when something on screen changed, unless drawing continuously.
+        let continuous = opt.continuous || frame < opt.capture_frames;
+        if continuous || needs_redraw {
+            needs_redraw = false;
+            glh::update_timers(&mut app.gl);
+            let clear_color = pipeline.shader_color(display.background_color());
+            unsafe {
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                gl::ClearColor(clear_color.r, clear_color.g, clear_color.b, clear_color.a);
+                gl::Viewport(0, 0, app.framebuffer_width as i32, app.framebuffer_height as i32);
+
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, tex);
+
+                // Draw text with no depth test and alpha blending.
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Enable(gl::BLEND);
+            }
+
+            match view_mode {
+                ViewMode::Text => {
+                    draw_text(
+                        &app, &app.writer, &shaders, &text_camera, &effects, &sdf, &pipeline,
+                        display.text_color(), text_layout.point_count()
+                    );
+                    update_text_debug_overlay(&app, &mut debug, &atlas, &text_layout, &placement, &pipeline)?;
+                    if debug.overlays.any() {
+                        lines::draw_lines(&line_shader, &debug.writer, &text_camera);
+                    }
+                    if editor.editing {
+                        let color = pipeline.overlay_color(display.text_color());
+                        update_caret_overlay(&app, &mut caret, &editor, &text_layout, &placement, color)?;
+                        lines::draw_lines(&line_shader, &caret.writer, &text_camera);
+                    }
+                    update_glyph_hover(&app, &mut hover, &atlas, &text_layout, &text_camera, &pipeline)?;
+                    draw_screen_panel(&app, &hover.panel, &shaders, &line_shader, &sdf, &pipeline, &effects);
+                }
+                ViewMode::Atlas => {
+                    draw_atlas_inspector(&inspector, &shaders.bitmap, &line_shader, &pipeline);
+                }
+            }
+            if help.visible {
+                let status = help_status(&display, &debug, &hud);
+                update_help_overlay(&app, &mut help, status, &atlas, &pipeline)?;
+                draw_screen_panel(&app, &help.panel, &shaders, &line_shader, &sdf, &pipeline, &effects);
+            }
+            update_performance_hud(&mut app, &mut hud, &atlas, &text_layout, &pipeline)?;
+            if hud.mode == HudMode::Overlay {
+                draw_screen_panel(&app, &hud.panel, &shaders, &line_shader, &sdf, &pipeline, &effects);
+            }
+            if error.message.is_some() {
+                update_error_banner(&app, &mut error, &atlas, &pipeline)?;
+                draw_screen_panel(&app, &error.panel, &shaders, &line_shader, &sdf, &pipeline, &effects);
+            }
+
+            // Screenshots are read from the back buffer, so take them before it is swapped.
+            if frame < opt.capture_frames {
+                take_screenshot(&app, input_path, &format!("frame_{:04}", frame));
+            }
+            if screenshot_requested {
+                take_screenshot(&app, input_path, &screenshot::timestamp());
+                screenshot_requested = false;
+            }
+            frame += 1;
+
+            // Send the results to the output.
+            app.gl.window.swap_buffers();
+            record_frame_time(&mut app, &mut hud);
+        }
+
+        if continuous {
+            app.gl.glfw.poll_events();
+        } else {
+            // Wake up now and then even without any input to check the watched files.
+            app.gl.glfw.wait_events_timeout(WAIT_EVENTS_TIMEOUT_SECONDS);
+        }
+        // Collect the events first, since handling them can need the whole app.
+        let events: Vec<_> = glfw::flush_messages(&app.gl.events).collect();
+        for (_, event) in events {
+            // Any input can change what is on screen, such as the glyph under the cursor.
+            needs_redraw = true;
+            if update_window_size(&mut app, &event) {
+                resized = true;
+            }
+            if let glfw::WindowEvent::Key(Key::F12, _, Action::Press, _) = event {
+                screenshot_requested = true;
+            }
+            if editor.editing {
+                match editor::update_text_editor(&mut editor, &atlas, &event) {
+                    EditResult::TextChanged => {
+                        text_layout = text_to_screen(&mut app, &atlas, placement, &editor.text)?;
+                        hover.panel.dirty = true;
+                        debug.dirty = true;
+                        caret.dirty = true;
+                    }
+                    EditResult::CaretMoved => {
+                        caret.dirty = true;
+                    }
+                    EditResult::Ignored => {}
+                }
+                // While editing, the keyboard types into the text instead of triggering hotkeys.
+                match event {
+                    glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
+                        editor.editing = false;
+                        println!("Stopped editing the text");
+                        continue;
+                    }
+                    glfw::WindowEvent::Key(..) | glfw::WindowEvent::Char(..) => continue,
+                    _ => {}
+                }
+            }
+            match event {
+                glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
+                    app.gl.window.set_should_close(true);
+                }
+                glfw::WindowEvent::Key(Key::F2, _, Action::Press, _) => {
+                    editor.editing = true;
+                    caret.dirty = true;
+                    view_mode = ViewMode::Text;
+                    println!("Editing the text. Press Escape to stop editing");
+                }
+                _ => {}
+            }
+            if let glfw::WindowEvent::Key(Key::Tab, _, Action::Press, _) = event {
+                view_mode = match view_mode {
+                    ViewMode::Text => ViewMode::Atlas,
+                    ViewMode::Atlas => ViewMode::Text,
+                };
+            }
+            match view_mode {
+                ViewMode::Text => {
+                    if text_controller.handle_event(&mut text_camera, &app.gl.window, &event) {
+                        hover.panel.dirty = true;
+                    }
+                }
+                ViewMode::Atlas => {
+                    inspector.controller.handle_event(&mut inspector.camera, &app.gl.window, &event);
+                }
+            }
+            update_glyph_hover_input(&mut hover, &app.gl.window, &event);
+            update_text_debug_overlay_input(&mut debug, &event);
+            update_help_overlay_input(&mut help, &event);
+            update_performance_hud_input(&mut app, &mut hud, &event);
+            if update_display_settings(&mut display, &event) {
+                placement.scale_px = display.scale_px;
+                text_layout = text_to_screen(&mut app, &atlas, placement, &editor.text)?;
+                hover.panel.dirty = true;
+                debug.dirty = true;
+                caret.dirty = true;
+            }
+            update_text_effects(&mut effects, &event);
+            update_distance_field(&mut sdf, &event);
+            if update_texture_settings(&mut texture_settings, &event) {
+                texture::apply_texture_settings(tex, &texture_settings);
+                println!("Texture: {}", texture_settings);
+            }
+        }
     }
 
     Ok(())
@@ -449,5 +2239,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(_) => {}
     }
 
-    run_app(opt)
+    // The options for reading PNG grid fonts apply to the subcommands as well as the viewer.
+    let import_options = import_options_from_opt(&opt);
+    match opt.command {
+        Some(Command::Info { ref input_path, json }) => run_info(input_path, &import_options, json),
+        Some(Command::Validate { ref input_path, ref required, allow_shared_cells }) => {
+            let options = validate::ValidationOptions {
+                required: required.clone(),
+                allow_shared_cells: allow_shared_cells,
+            };
+            run_validate(input_path, &import_options, &options)
+        }
+        Some(Command::Coverage { ref input_path, all_blocks }) => {
+            let atlas = load_atlas(input_path, &import_options)?;
+            print!("{}", coverage::report(&atlas, all_blocks));
+            Ok(())
+        }
+        Some(Command::Check { ref input_path, ref files }) => run_check(input_path, &import_options, files),
+        Some(Command::Layout(ref layout_opt)) => run_layout(layout_opt, &import_options),
+        Some(Command::Export { ref input_path, format, ref output_path }) => {
+            let output_path = output_path.clone().unwrap_or_else(|| input_path.with_extension("fnt"));
+            run_export(input_path, &import_options, format, &output_path)
+        }
+        Some(Command::Generate(ref generate_opt)) => {
+            run_generate(generate_opt)?;
+            if !generate_opt.view {
+                return Ok(());
+            }
+            let output_path = generate_opt.output_path();
+            run_app(opt, &output_path)
+        }
+        None => {
+            let input_path = opt.input_path.clone().unwrap();
+            run_app(opt, &input_path)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_fails_when_problems_are_found() {
+        let path = Path::new("broken.bmfa");
+        let problems = [
+            validate::Problem::OutsideGrid { code_point: 65, row: 4, column: 0 },
+            validate::Problem::InkInPadding { code_point: 66, pixels: 3 },
+        ];
+
+        match report_problems(path, &problems) {
+            Err(AppError::InvalidFontAtlas(ref failed_path, 2)) => assert_eq!(failed_path.as_path(), path),
+            result => panic!("expected the validation to fail, got {:?}", result),
+        }
+        assert!(report_problems(path, &[]).is_ok());
+    }
 }
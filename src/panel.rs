@@ -0,0 +1,94 @@
+use crate::color::Color;
+use crate::layout;
+use crate::layout::{TextLayout, TextPlacement};
+use crate::lines::LineBatch;
+use crate::text_help;
+
+
+/// The space in pixels between the panel edges and its text.
+const PANEL_MARGIN_PX: f32 = 8.0;
+/// The distance in pixels between the window edges and a panel placed against them.
+const WINDOW_MARGIN_PX: f32 = 16.0;
+/// The distance in pixels between the cursor and a panel following it.
+const CURSOR_OFFSET_PX: f32 = 16.0;
+
+
+/// Where a panel sits in the window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    /// Across the bottom of the window. Long text wraps at the right edge of the window,
+    /// and the panel grows upwards to fit it.
+    Bottom,
+    /// Just below and to the right of the cursor, given in normalized device coordinates.
+    /// The panel moves to stay inside the window.
+    Cursor(f32, f32),
+}
+
+/// A block of text drawn in screen space over the scene: the laid out text, and the
+/// panel background behind it.
+pub struct Panel {
+    pub text: TextLayout,
+    pub overlay: LineBatch,
+}
+
+/// Build a panel in a window `width` by `height` pixels in size. The panel is drawn
+/// with the atlas itself, so characters the atlas has no glyph for are shown with
+/// a placeholder.
+pub fn panel(
+    atlas: &bmfa::BitmapFontAtlas, text: &str, scale_px: f32, anchor: Anchor,
+    width: u32, height: u32, background: Color) -> Panel {
+
+    let px_to_ndc_x = 2.0 / (width as f32);
+    let px_to_ndc_y = 2.0 / (height as f32);
+    let margin_x = PANEL_MARGIN_PX * px_to_ndc_x;
+    let margin_y = PANEL_MARGIN_PX * px_to_ndc_y;
+    let window_margin_x = WINDOW_MARGIN_PX * px_to_ndc_x;
+    let window_margin_y = WINDOW_MARGIN_PX * px_to_ndc_y;
+    let text = text_help::printable_text(atlas, text);
+
+    let mut placement = match anchor {
+        Anchor::TopLeft | Anchor::TopRight => TextPlacement::new(
+            -1.0 + window_margin_x + margin_x, 1.0 - window_margin_y - margin_y, std::f32::INFINITY, scale_px
+        ),
+        Anchor::Bottom => TextPlacement::new(-1.0 + margin_x, 1.0, 1.0 - 2.0 * margin_x, scale_px),
+        Anchor::Cursor(x, y) => TextPlacement::new(
+            x + CURSOR_OFFSET_PX * px_to_ndc_x + margin_x, y - CURSOR_OFFSET_PX * px_to_ndc_y - margin_y,
+            std::f32::INFINITY, scale_px
+        ),
+    };
+
+    // Only the text layout knows how large the text is, so lay it out once to find its
+    // bounds, then move it into place.
+    let mut text_layout = layout::layout_text(atlas, placement, width, height, &text);
+    if let Some((_, _, right, bottom)) = text_help::text_bounds(&text_layout.glyphs) {
+        let (shift_x, shift_y) = match anchor {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::TopRight => ((1.0 - window_margin_x - margin_x) - right, 0.0),
+            Anchor::Bottom => (0.0, (-1.0 + margin_y) - bottom),
+            Anchor::Cursor(..) => (
+                f32::min(1.0 - (right + margin_x), 0.0),
+                f32::max(-1.0 - (bottom - margin_y), 0.0),
+            ),
+        };
+        if shift_x != 0.0 || shift_y != 0.0 {
+            placement.start_at_x = f32::max(placement.start_at_x + shift_x, -1.0 + margin_x);
+            placement.start_at_y += shift_y;
+            text_layout = layout::layout_text(atlas, placement, width, height, &text);
+        }
+    }
+
+    let mut overlay = LineBatch::new();
+    if let Some((left, top, right, bottom)) = text_help::text_bounds(&text_layout.glyphs) {
+        match anchor {
+            Anchor::Bottom => overlay.push_filled_rect(-1.0, top + margin_y, 1.0, -1.0, background),
+            _ => overlay.push_filled_rect(left - margin_x, top + margin_y, right + margin_x, bottom - margin_y, background),
+        }
+    }
+
+    Panel {
+        text: text_layout,
+        overlay: overlay,
+    }
+}
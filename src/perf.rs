@@ -0,0 +1,151 @@
+use crate::color::Color;
+use crate::layout::TextLayout;
+use crate::panel;
+use crate::panel::{Anchor, Panel};
+
+use std::error;
+use std::fmt;
+use std::str;
+
+
+/// How often the frame statistics are averaged, in seconds.
+const STATS_INTERVAL_SECONDS: f64 = 0.5;
+/// The size of the text in the performance overlay.
+const HUD_SCALE_PX: f32 = 32.0;
+
+
+#[derive(Clone, Debug)]
+pub struct ParseHudModeError {
+    input: String,
+}
+
+impl fmt::Display for ParseHudModeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid performance display `{}`. Expected one of: off, title, overlay.", self.input)
+    }
+}
+
+impl error::Error for ParseHudModeError {}
+
+/// Where the performance statistics are shown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HudMode {
+    Off,
+    /// In the window title bar.
+    Title,
+    /// In a panel in the top right corner of the window.
+    Overlay,
+}
+
+impl HudMode {
+    pub fn next(self) -> HudMode {
+        match self {
+            HudMode::Off => HudMode::Title,
+            HudMode::Title => HudMode::Overlay,
+            HudMode::Overlay => HudMode::Off,
+        }
+    }
+}
+
+impl fmt::Display for HudMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HudMode::Off => write!(f, "off"),
+            HudMode::Title => write!(f, "title"),
+            HudMode::Overlay => write!(f, "overlay"),
+        }
+    }
+}
+
+impl str::FromStr for HudMode {
+    type Err = ParseHudModeError;
+
+    fn from_str(st: &str) -> Result<HudMode, ParseHudModeError> {
+        match st {
+            "off" => Ok(HudMode::Off),
+            "title" => Ok(HudMode::Title),
+            "overlay" => Ok(HudMode::Overlay),
+            _ => Err(ParseHudModeError { input: st.to_string() }),
+        }
+    }
+}
+
+/// Frame timing averaged over short intervals, along with the size of the text
+/// being drawn. A frame is timed from the start of drawing it to after its buffers
+/// are swapped, leaving out the time the viewer spends waiting for events between
+/// frames, so the frame rate is how fast frames can be drawn rather than how often
+/// they are. With vertical sync the swap waits for the display, which caps the frame
+/// rate at its refresh rate.
+#[derive(Copy, Clone, Debug)]
+pub struct FrameStats {
+    /// The average time to draw a frame over the last interval.
+    pub frame_time_seconds: f64,
+    pub fps: f64,
+    pub glyph_count: usize,
+    /// The size of the text vertex data uploaded to the GPU.
+    pub vertex_bytes: usize,
+    interval_start_seconds: f64,
+    interval_frame_seconds: f64,
+    interval_frames: u32,
+}
+
+impl FrameStats {
+    pub fn new() -> FrameStats {
+        FrameStats {
+            frame_time_seconds: 0.0,
+            fps: 0.0,
+            glyph_count: 0,
+            vertex_bytes: 0,
+            interval_start_seconds: 0.0,
+            interval_frame_seconds: 0.0,
+            interval_frames: 0,
+        }
+    }
+
+    /// Record a frame that took `frame_seconds` to draw, finishing at `now_seconds`.
+    /// Returns `true` when the averages were updated.
+    pub fn update(&mut self, now_seconds: f64, frame_seconds: f64) -> bool {
+        self.interval_frame_seconds += frame_seconds;
+        self.interval_frames += 1;
+        if now_seconds - self.interval_start_seconds < STATS_INTERVAL_SECONDS {
+            return false;
+        }
+
+        self.frame_time_seconds = self.interval_frame_seconds / (self.interval_frames as f64);
+        self.fps = if self.frame_time_seconds > 0.0 { 1.0 / self.frame_time_seconds } else { 0.0 };
+        self.interval_start_seconds = now_seconds;
+        self.interval_frame_seconds = 0.0;
+        self.interval_frames = 0;
+
+        true
+    }
+
+    /// Record the size of the text being drawn.
+    pub fn set_text(&mut self, text_layout: &TextLayout) {
+        self.glyph_count = text_layout.glyphs.len();
+        self.vertex_bytes = text_layout.vertex_bytes();
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{:.1} FPS, {:.2} ms, {} glyphs, {} vertex bytes",
+            self.fps, 1000.0 * self.frame_time_seconds, self.glyph_count, self.vertex_bytes
+        )
+    }
+}
+
+/// Build the performance overlay in the top right corner of a window `width` by `height`
+/// pixels in size, one statistic per line.
+pub fn hud_panel(
+    atlas: &bmfa::BitmapFontAtlas, stats: &FrameStats,
+    width: u32, height: u32, background: Color) -> Panel {
+
+    let stats_text = format!(
+        "{:.1} FPS\n{:.2} ms\n{} glyphs\n{} vertex bytes",
+        stats.fps, 1000.0 * stats.frame_time_seconds, stats.glyph_count, stats.vertex_bytes
+    );
+
+    panel::panel(atlas, &stats_text, HUD_SCALE_PX, Anchor::TopRight, width, height, background)
+}
@@ -0,0 +1,57 @@
+use crate::gl;
+use crate::gl::types::{GLsizei, GLvoid};
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+/// Read the back buffer into an RGBA image with its top row first. The window is
+/// opaque on screen, so the alpha channel is made opaque as well rather than saving
+/// whatever alpha blending left in the framebuffer.
+pub fn read_framebuffer(width: u32, height: u32) -> Vec<u8> {
+    let row_size = 4 * width as usize;
+    let mut pixels = vec![0u8; row_size * height as usize];
+    unsafe {
+        gl::ReadBuffer(gl::BACK);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0, 0, width as GLsizei, height as GLsizei,
+            gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut GLvoid
+        );
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 4);
+    }
+
+    // OpenGL returns the bottom row first.
+    let mut image = Vec::with_capacity(pixels.len());
+    for row in pixels.chunks(row_size).rev() {
+        image.extend_from_slice(row);
+    }
+    for pixel in image.chunks_mut(4) {
+        pixel[3] = 255;
+    }
+
+    image
+}
+
+/// The path of a screenshot saved next to the font atlas. Screenshots are named after
+/// the atlas with a label appended, such as the time the screenshot was taken.
+pub fn screenshot_path(atlas_path: &Path, label: &str) -> PathBuf {
+    let stem = atlas_path.file_stem().map_or(String::from("fontview"), |stem| stem.to_string_lossy().into_owned());
+
+    atlas_path.with_file_name(format!("{}_{}.png", stem, label))
+}
+
+/// A label for a screenshot from the current time in milliseconds since the Unix epoch.
+pub fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    format!("{}{:03}", now.as_secs(), now.subsec_millis())
+}
+
+/// Save the back buffer of a window `width` by `height` pixels in size as a PNG file.
+pub fn save_screenshot(path: &Path, width: u32, height: u32) -> io::Result<()> {
+    let image = read_framebuffer(width, height);
+
+    image::save_buffer(path, &image, width, height, image::RGBA(8))
+}
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+
+/// Build a blank font atlas `columns` by `rows` slots of `slot` pixels in size, with
+/// a glyph in the cell at each `(code_point, row, column)`. Each glyph's quad covers
+/// its whole slot, and its advance is half a slot.
+pub fn atlas(
+    columns: usize, rows: usize, slot: usize, padding: usize,
+    glyphs: &[(usize, usize, usize)]) -> bmfa::BitmapFontAtlas {
+
+    let (width, height) = (columns * slot, rows * slot);
+    let glyph_metadata = glyphs.iter()
+        .map(|&(code_point, row, column)| {
+            let metadata = bmfa::GlyphMetadata {
+                code_point: code_point,
+                row: row,
+                column: column,
+                x_min: ((column * slot) as f32) / (width as f32),
+                y_min: ((row * slot) as f32) / (height as f32),
+                width: 0.5,
+                height: 1.0,
+                y_offset: 0.0,
+            };
+            (code_point, metadata)
+        })
+        .collect::<HashMap<_, _>>();
+
+    bmfa::BitmapFontAtlas {
+        origin: bmfa::Origin::BottomLeft,
+        width: width,
+        height: height,
+        columns: columns,
+        rows: rows,
+        padding: padding,
+        slot_glyph_size: slot,
+        glyph_size: slot - padding,
+        glyph_metadata: glyph_metadata,
+        image: vec![0; 4 * width * height],
+    }
+}
@@ -0,0 +1,33 @@
+use crate::layout::PlacedGlyph;
+
+
+/// Replace the characters the atlas has no glyph for, so the text can be drawn
+/// with the atlas itself.
+pub fn printable_text(atlas: &bmfa::BitmapFontAtlas, st: &str) -> String {
+    let fallback = if atlas.glyph_metadata.contains_key(&('?' as usize)) { Some('?') } else { None };
+
+    st.chars()
+        .filter_map(|ch| {
+            if ch == '\n' || atlas.glyph_metadata.contains_key(&(ch as usize)) {
+                Some(ch)
+            } else {
+                fallback
+            }
+        })
+        .collect()
+}
+
+/// The bounding box of a laid out block of text in normalized device coordinates,
+/// as `(left, top, right, bottom)`.
+pub fn text_bounds(glyphs: &[PlacedGlyph]) -> Option<(f32, f32, f32, f32)> {
+    let first = glyphs.first()?;
+    let mut bounds = (first.left, first.top, first.left + first.advance, first.bottom);
+    for glyph in glyphs.iter() {
+        bounds.0 = f32::min(bounds.0, glyph.left);
+        bounds.1 = f32::max(bounds.1, glyph.top);
+        bounds.2 = f32::max(bounds.2, glyph.left + glyph.advance);
+        bounds.3 = f32::min(bounds.3, glyph.bottom);
+    }
+
+    Some(bounds)
+}
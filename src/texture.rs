@@ -0,0 +1,372 @@
+use crate::color;
+use crate::gl;
+use crate::gl::types::{GLenum, GLfloat, GLint, GLuint, GLvoid};
+
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+use std::str;
+
+
+// OpenGL extension constants.
+const GL_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FE;
+const GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
+
+
+#[derive(Clone, Debug)]
+pub struct ParseTextureOptionError {
+    kind: &'static str,
+    input: String,
+    expected: &'static str,
+}
+
+impl fmt::Display for ParseTextureOptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid {} `{}`. Expected one of: {}.", self.kind, self.input, self.expected)
+    }
+}
+
+impl error::Error for ParseTextureOptionError {}
+
+/// The texture magnification filter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MagFilter {
+    Nearest,
+    Linear,
+}
+
+impl MagFilter {
+    fn to_gl(self) -> GLenum {
+        match self {
+            MagFilter::Nearest => gl::NEAREST,
+            MagFilter::Linear => gl::LINEAR,
+        }
+    }
+
+    pub fn next(self) -> MagFilter {
+        match self {
+            MagFilter::Nearest => MagFilter::Linear,
+            MagFilter::Linear => MagFilter::Nearest,
+        }
+    }
+}
+
+impl fmt::Display for MagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MagFilter::Nearest => write!(f, "nearest"),
+            MagFilter::Linear => write!(f, "linear"),
+        }
+    }
+}
+
+impl str::FromStr for MagFilter {
+    type Err = ParseTextureOptionError;
+
+    fn from_str(st: &str) -> Result<MagFilter, ParseTextureOptionError> {
+        match st {
+            "nearest" => Ok(MagFilter::Nearest),
+            "linear" => Ok(MagFilter::Linear),
+            _ => Err(ParseTextureOptionError {
+                kind: "magnification filter",
+                input: st.to_string(),
+                expected: "nearest, linear",
+            }),
+        }
+    }
+}
+
+/// The texture minification filter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MinFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl MinFilter {
+    /// The OpenGL filter to use. Without mipmaps the mipmap filters fall back to
+    /// sampling the base level, since the texture would otherwise be incomplete.
+    fn to_gl(self, mipmaps: bool) -> GLenum {
+        match (self, mipmaps) {
+            (MinFilter::Nearest, _) => gl::NEAREST,
+            (MinFilter::Linear, _) => gl::LINEAR,
+            (MinFilter::NearestMipmapNearest, true) => gl::NEAREST_MIPMAP_NEAREST,
+            (MinFilter::LinearMipmapNearest, true) => gl::LINEAR_MIPMAP_NEAREST,
+            (MinFilter::NearestMipmapLinear, true) => gl::NEAREST_MIPMAP_LINEAR,
+            (MinFilter::LinearMipmapLinear, true) => gl::LINEAR_MIPMAP_LINEAR,
+            (MinFilter::NearestMipmapNearest, false) => gl::NEAREST,
+            (MinFilter::NearestMipmapLinear, false) => gl::NEAREST,
+            (MinFilter::LinearMipmapNearest, false) => gl::LINEAR,
+            (MinFilter::LinearMipmapLinear, false) => gl::LINEAR,
+        }
+    }
+
+    pub fn next(self) -> MinFilter {
+        match self {
+            MinFilter::Nearest => MinFilter::Linear,
+            MinFilter::Linear => MinFilter::NearestMipmapNearest,
+            MinFilter::NearestMipmapNearest => MinFilter::LinearMipmapNearest,
+            MinFilter::LinearMipmapNearest => MinFilter::NearestMipmapLinear,
+            MinFilter::NearestMipmapLinear => MinFilter::LinearMipmapLinear,
+            MinFilter::LinearMipmapLinear => MinFilter::Nearest,
+        }
+    }
+}
+
+impl fmt::Display for MinFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MinFilter::Nearest => write!(f, "nearest"),
+            MinFilter::Linear => write!(f, "linear"),
+            MinFilter::NearestMipmapNearest => write!(f, "nearest-mipmap-nearest"),
+            MinFilter::LinearMipmapNearest => write!(f, "linear-mipmap-nearest"),
+            MinFilter::NearestMipmapLinear => write!(f, "nearest-mipmap-linear"),
+            MinFilter::LinearMipmapLinear => write!(f, "linear-mipmap-linear"),
+        }
+    }
+}
+
+impl str::FromStr for MinFilter {
+    type Err = ParseTextureOptionError;
+
+    fn from_str(st: &str) -> Result<MinFilter, ParseTextureOptionError> {
+        match st {
+            "nearest" => Ok(MinFilter::Nearest),
+            "linear" => Ok(MinFilter::Linear),
+            "nearest-mipmap-nearest" => Ok(MinFilter::NearestMipmapNearest),
+            "linear-mipmap-nearest" => Ok(MinFilter::LinearMipmapNearest),
+            "nearest-mipmap-linear" => Ok(MinFilter::NearestMipmapLinear),
+            "linear-mipmap-linear" => Ok(MinFilter::LinearMipmapLinear),
+            _ => Err(ParseTextureOptionError {
+                kind: "minification filter",
+                input: st.to_string(),
+                expected: "nearest, linear, nearest-mipmap-nearest, linear-mipmap-nearest, \
+                    nearest-mipmap-linear, linear-mipmap-linear",
+            }),
+        }
+    }
+}
+
+/// The texture coordinate wrapping mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    ClampToEdge,
+    ClampToBorder,
+    Repeat,
+    MirroredRepeat,
+}
+
+impl WrapMode {
+    fn to_gl(self) -> GLenum {
+        match self {
+            WrapMode::ClampToEdge => gl::CLAMP_TO_EDGE,
+            WrapMode::ClampToBorder => gl::CLAMP_TO_BORDER,
+            WrapMode::Repeat => gl::REPEAT,
+            WrapMode::MirroredRepeat => gl::MIRRORED_REPEAT,
+        }
+    }
+
+    pub fn next(self) -> WrapMode {
+        match self {
+            WrapMode::ClampToEdge => WrapMode::ClampToBorder,
+            WrapMode::ClampToBorder => WrapMode::Repeat,
+            WrapMode::Repeat => WrapMode::MirroredRepeat,
+            WrapMode::MirroredRepeat => WrapMode::ClampToEdge,
+        }
+    }
+}
+
+impl fmt::Display for WrapMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WrapMode::ClampToEdge => write!(f, "clamp-to-edge"),
+            WrapMode::ClampToBorder => write!(f, "clamp-to-border"),
+            WrapMode::Repeat => write!(f, "repeat"),
+            WrapMode::MirroredRepeat => write!(f, "mirrored-repeat"),
+        }
+    }
+}
+
+impl str::FromStr for WrapMode {
+    type Err = ParseTextureOptionError;
+
+    fn from_str(st: &str) -> Result<WrapMode, ParseTextureOptionError> {
+        match st {
+            "clamp-to-edge" => Ok(WrapMode::ClampToEdge),
+            "clamp-to-border" => Ok(WrapMode::ClampToBorder),
+            "repeat" => Ok(WrapMode::Repeat),
+            "mirrored-repeat" => Ok(WrapMode::MirroredRepeat),
+            _ => Err(ParseTextureOptionError {
+                kind: "wrap mode",
+                input: st.to_string(),
+                expected: "clamp-to-edge, clamp-to-border, repeat, mirrored-repeat",
+            }),
+        }
+    }
+}
+
+/// The sampling parameters for the font atlas texture.
+#[derive(Copy, Clone, Debug)]
+pub struct TextureSettings {
+    pub min_filter: MinFilter,
+    pub mag_filter: MagFilter,
+    pub mipmaps: bool,
+    /// The anisotropic filtering level. The driver maximum is used when this is `None`.
+    pub anisotropy: Option<f32>,
+    pub wrap: WrapMode,
+}
+
+impl TextureSettings {
+    /// Step through the anisotropic filtering levels 1, 2, 4, 8 and 16, wrapping
+    /// around past the driver maximum.
+    pub fn next_anisotropy(&mut self) {
+        let max_aniso = max_anisotropy();
+        let current = self.anisotropy.unwrap_or(max_aniso);
+        let next = current * 2.0;
+        self.anisotropy = if next > max_aniso || next > 16.0 { Some(1.0) } else { Some(next) };
+    }
+}
+
+impl fmt::Display for TextureSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "min filter: {}, mag filter: {}, mipmaps: {}, anisotropy: ",
+            self.min_filter, self.mag_filter, if self.mipmaps { "on" } else { "off" }
+        )?;
+        match self.anisotropy {
+            Some(level) => write!(f, "{}x", level)?,
+            None => write!(f, "max")?,
+        }
+        write!(f, ", wrap: {}", self.wrap)
+    }
+}
+
+/// How the atlas image is stored on the GPU.
+#[derive(Copy, Clone, Debug)]
+pub struct TextureFormat {
+    /// Store the image in an sRGB texture so the color channels are decoded to
+    /// linear space when sampled.
+    pub srgb: bool,
+    /// Multiply the color channels by alpha before uploading the image.
+    pub premultiplied_alpha: bool,
+}
+
+/// Multiply the color channels of an RGBA image by its alpha channel. For sRGB
+/// images the multiplication is done in linear space and the result re-encoded.
+fn premultiply_alpha(image: &[u8], srgb: bool) -> Vec<u8> {
+    let mut premultiplied = Vec::with_capacity(image.len());
+    for pixel in image.chunks(4) {
+        if pixel.len() < 4 {
+            premultiplied.extend_from_slice(pixel);
+            continue;
+        }
+
+        let alpha = pixel[3] as f32 / 255.0;
+        for &channel in &pixel[0..3] {
+            let value = channel as f32 / 255.0;
+            let value = if srgb {
+                color::linear_to_srgb(color::srgb_to_linear(value) * alpha)
+            } else {
+                value * alpha
+            };
+            premultiplied.push((value * 255.0 + 0.5) as u8);
+        }
+        premultiplied.push(pixel[3]);
+    }
+
+    premultiplied
+}
+
+/// Query the largest anisotropic filtering level the driver supports. This is
+/// `1.0` when anisotropic filtering is not available.
+fn max_anisotropy() -> f32 {
+    let mut max_aniso: GLfloat = 0.0;
+    unsafe {
+        gl::GetFloatv(GL_MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_aniso);
+    }
+
+    f32::max(max_aniso, 1.0)
+}
+
+/// Load texture image into the GPU.
+pub fn load_font_texture(
+    atlas: &bmfa::BitmapFontAtlas,
+    format: &TextureFormat, settings: &TextureSettings) -> Result<GLuint, String> {
+
+    let image = if format.premultiplied_alpha {
+        Cow::Owned(premultiply_alpha(&atlas.image, format.srgb))
+    } else {
+        Cow::Borrowed(&atlas.image[..])
+    };
+    let internal_format = if format.srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 };
+
+    let mut tex = 0;
+    unsafe {
+        gl::GenTextures(1, &mut tex);
+    }
+    assert!(tex > 0);
+
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, tex);
+        gl::TexImage2D(
+            gl::TEXTURE_2D, 0, internal_format as i32, atlas.width as i32, atlas.height as i32, 0,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            image.as_ptr() as *const GLvoid
+        );
+    }
+
+    apply_texture_settings(tex, settings);
+
+    Ok(tex)
+}
+
+/// Set the sampling parameters on a texture. Mipmaps are generated when they
+/// are enabled, so this can be called again whenever the settings change.
+pub fn apply_texture_settings(tex: GLuint, settings: &TextureSettings) {
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_2D, tex);
+        if settings.mipmaps {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 1000);
+        } else {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
+        }
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, settings.wrap.to_gl() as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, settings.wrap.to_gl() as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, settings.mag_filter.to_gl() as GLint);
+        gl::TexParameteri(
+            gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, settings.min_filter.to_gl(settings.mipmaps) as GLint
+        );
+    }
+
+    let max_aniso = max_anisotropy();
+    let aniso = match settings.anisotropy {
+        Some(level) => f32::min(f32::max(level, 1.0), max_aniso),
+        None => max_aniso,
+    };
+    unsafe {
+        gl::TexParameterf(gl::TEXTURE_2D, GL_TEXTURE_MAX_ANISOTROPY_EXT, aniso);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn premultiply_partly_transparent_pixel() {
+        let image = [255, 128, 0, 128, 10, 20, 30, 255];
+
+        // The fully opaque second pixel is left alone.
+        assert_eq!(premultiply_alpha(&image, false), vec![128, 64, 0, 128, 10, 20, 30, 255]);
+        // In sRGB space the color channels are scaled in linear space, so they stay brighter.
+        assert_eq!(premultiply_alpha(&image, true), vec![188, 93, 0, 128, 10, 20, 30, 255]);
+    }
+}
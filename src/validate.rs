@@ -0,0 +1,321 @@
+use crate::charset::CharacterSet;
+use crate::encoding;
+use crate::encoding::GlyphEncoding;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+
+/// A problem found in a font atlas.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    /// A glyph is stored under a different code point than the one in its metadata.
+    CodePointMismatch { key: usize, code_point: usize },
+    /// A glyph's row or column lies outside the atlas grid.
+    OutsideGrid { code_point: usize, row: usize, column: usize },
+    /// A glyph metric is not a number, infinite, or negative where it cannot be.
+    InvalidMetric { code_point: usize, name: &'static str, value: f32 },
+    /// The atlas image is not the size the grid says it is.
+    ImageSize { expected_width: usize, expected_height: usize, width: usize, height: usize },
+    /// The atlas image data does not hold `width * height` RGBA pixels.
+    ImageData { expected_len: usize, len: usize },
+    /// Several code points are drawn from the same grid cell.
+    SharedCell { row: usize, column: usize, code_points: Vec<usize> },
+    /// A required character set is not covered.
+    MissingCharacters { set: CharacterSet, code_points: Vec<usize> },
+    /// Part of a glyph's ink lies in the padding between it and the next cells, so it
+    /// can bleed into its neighbors when the atlas is sampled.
+    InkInPadding { code_point: usize, pixels: usize },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Problem::CodePointMismatch { key, code_point } => {
+                write!(f, "The glyph stored under U+{:04X} has the code point U+{:04X}.", key, code_point)
+            }
+            Problem::OutsideGrid { code_point, row, column } => {
+                write!(
+                    f, "U+{:04X} is at row {} column {}, which is outside the atlas grid.",
+                    code_point, row, column
+                )
+            }
+            Problem::InvalidMetric { code_point, name, value } => {
+                write!(f, "U+{:04X} has an invalid {}: {}.", code_point, name, value)
+            }
+            Problem::ImageSize { expected_width, expected_height, width, height } => {
+                write!(
+                    f, "The atlas image is {}x{}, but the grid needs {}x{}.",
+                    width, height, expected_width, expected_height
+                )
+            }
+            Problem::ImageData { expected_len, len } => {
+                write!(f, "The atlas image has {} bytes of pixel data, but should have {}.", len, expected_len)
+            }
+            Problem::SharedCell { row, column, ref code_points } => {
+                write!(f, "{} share the cell at row {} column {}.", code_point_list(code_points), row, column)
+            }
+            Problem::MissingCharacters { set, ref code_points } => {
+                write!(
+                    f, "The atlas is missing {} characters of {}: {}.",
+                    code_points.len(), set, code_point_list(code_points)
+                )
+            }
+            Problem::InkInPadding { code_point, pixels } => {
+                write!(f, "U+{:04X} has {} inked pixels in the padding of its cell.", code_point, pixels)
+            }
+        }
+    }
+}
+
+/// Format a list of code points as `U+0041, U+0042, ...`.
+pub fn code_point_list(code_points: &[usize]) -> String {
+    code_points.iter()
+        .map(|code_point| format!("U+{:04X}", code_point))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// What to check an atlas for.
+#[derive(Clone, Debug)]
+pub struct ValidationOptions {
+    /// The character sets the atlas must cover.
+    pub required: Vec<CharacterSet>,
+    /// Allow several code points to use the same glyph, such as a space and a
+    /// non-breaking space.
+    pub allow_shared_cells: bool,
+}
+
+/// Check that the glyph metrics are usable. Every metric must be finite, and the
+/// size of the glyph cannot be negative. The offsets can be negative, since glyphs
+/// such as `g` hang below the baseline.
+fn check_metrics(metadata: &bmfa::GlyphMetadata, problems: &mut Vec<Problem>) {
+    let metrics = [
+        ("x_min", metadata.x_min, false),
+        ("y_min", metadata.y_min, false),
+        ("width", metadata.width, true),
+        ("height", metadata.height, true),
+        ("y_offset", metadata.y_offset, false),
+    ];
+    for &(name, value, non_negative) in metrics.iter() {
+        if !value.is_finite() || (non_negative && value < 0.0) {
+            problems.push(Problem::InvalidMetric {
+                code_point: metadata.code_point as usize,
+                name: name,
+                value: value,
+            });
+        }
+    }
+}
+
+/// Count the inked pixels in the padding of a glyph's cell. Glyphs are drawn from the
+/// top left corner of their cell, and the padding is the band `padding` pixels wide
+/// along the right and bottom edges that separates them from the next cells. Rows are
+/// counted from the top of the atlas, while the image is stored bottom row first, so
+/// the bottom band of a cell comes first in the image.
+fn ink_in_padding(atlas: &bmfa::BitmapFontAtlas, metadata: &bmfa::GlyphMetadata) -> usize {
+    let slot = atlas.slot_glyph_size as usize;
+    let padding = atlas.padding as usize;
+    let image_width = atlas.width as usize;
+    let cell_x = (metadata.column as usize) * slot;
+    let cell_y = ((atlas.rows as usize) - 1 - (metadata.row as usize)) * slot;
+
+    let mut pixels = 0;
+    for y in 0..slot {
+        for x in 0..slot {
+            let in_padding = x >= slot - padding || y < padding;
+            if !in_padding {
+                continue;
+            }
+            let alpha = atlas.image[4 * ((cell_y + y) * image_width + cell_x + x) + 3];
+            if alpha > 0 {
+                pixels += 1;
+            }
+        }
+    }
+
+    pixels
+}
+
+/// Check a font atlas for problems that would make it draw incorrectly. Problems
+/// with individual glyphs are listed in code point order.
+pub fn validate(atlas: &bmfa::BitmapFontAtlas, options: &ValidationOptions) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let rows = atlas.rows as usize;
+    let columns = atlas.columns as usize;
+    let slot = atlas.slot_glyph_size as usize;
+    let (width, height) = (atlas.width as usize, atlas.height as usize);
+
+    let expected_width = columns * slot;
+    let expected_height = rows * slot;
+    let image_size_ok = width == expected_width && height == expected_height;
+    if !image_size_ok {
+        problems.push(Problem::ImageSize {
+            expected_width: expected_width,
+            expected_height: expected_height,
+            width: width,
+            height: height,
+        });
+    }
+    let image_data_ok = atlas.image.len() == 4 * width * height;
+    if !image_data_ok {
+        problems.push(Problem::ImageData { expected_len: 4 * width * height, len: atlas.image.len() });
+    }
+
+    // Distance fields deliberately spread past the glyph edges into the padding, so only
+    // coverage bitmaps are checked for ink there.
+    let check_ink = image_size_ok && image_data_ok && (atlas.padding as usize) < slot &&
+        encoding::detect_glyph_encoding(atlas) == GlyphEncoding::Bitmap;
+
+    let mut code_points: Vec<usize> = atlas.glyph_metadata.keys().cloned().collect();
+    code_points.sort();
+    let mut cells: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for &code_point in code_points.iter() {
+        let metadata = &atlas.glyph_metadata[&code_point];
+        if metadata.code_point as usize != code_point {
+            problems.push(Problem::CodePointMismatch { key: code_point, code_point: metadata.code_point as usize });
+        }
+        check_metrics(metadata, &mut problems);
+
+        let (row, column) = (metadata.row as usize, metadata.column as usize);
+        if row >= rows || column >= columns {
+            problems.push(Problem::OutsideGrid { code_point: code_point, row: row, column: column });
+            continue;
+        }
+        cells.entry((row, column)).or_insert_with(Vec::new).push(code_point);
+
+        if check_ink {
+            let pixels = ink_in_padding(atlas, metadata);
+            if pixels > 0 {
+                problems.push(Problem::InkInPadding { code_point: code_point, pixels: pixels });
+            }
+        }
+    }
+
+    if !options.allow_shared_cells {
+        for ((row, column), code_points) in cells {
+            if code_points.len() > 1 {
+                problems.push(Problem::SharedCell { row: row, column: column, code_points: code_points });
+            }
+        }
+    }
+
+    for &set in options.required.iter() {
+        let missing = set.missing(atlas);
+        if !missing.is_empty() {
+            problems.push(Problem::MissingCharacters { set: set, code_points: missing });
+        }
+    }
+
+    problems
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_atlas;
+    use std::path::Path;
+
+    fn options() -> ValidationOptions {
+        ValidationOptions { required: vec![], allow_shared_cells: false }
+    }
+
+    /// Ink a pixel of the atlas image, with `y` counted from the top of the atlas.
+    fn ink(atlas: &mut bmfa::BitmapFontAtlas, x: usize, y: usize) {
+        let y = atlas.height - 1 - y;
+        atlas.image[4 * (y * atlas.width + x) + 3] = 255;
+    }
+
+    #[test]
+    fn sample_atlas_validates_clean() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join("freemono.bmfa");
+        let atlas = bmfa::load(&path).unwrap();
+        let options = ValidationOptions {
+            required: vec![CharacterSet::PrintableAscii],
+            allow_shared_cells: false,
+        };
+
+        assert_eq!(validate(&atlas, &options), vec![]);
+    }
+
+    #[test]
+    fn glyph_outside_the_grid() {
+        let atlas = test_atlas::atlas(2, 1, 8, 2, &[(65, 0, 1), (66, 1, 0)]);
+
+        assert_eq!(validate(&atlas, &options()), vec![Problem::OutsideGrid { code_point: 66, row: 1, column: 0 }]);
+    }
+
+    #[test]
+    fn invalid_metrics() {
+        let mut atlas = test_atlas::atlas(2, 1, 8, 2, &[(65, 0, 0), (66, 0, 1)]);
+        atlas.glyph_metadata.get_mut(&65).unwrap().y_offset = std::f32::NAN;
+        atlas.glyph_metadata.get_mut(&66).unwrap().width = -0.5;
+        // A negative offset is fine, since glyphs can hang below the baseline.
+        atlas.glyph_metadata.get_mut(&66).unwrap().y_offset = -0.25;
+
+        let problems = validate(&atlas, &options());
+        assert_eq!(problems.len(), 2);
+        match problems[0] {
+            Problem::InvalidMetric { code_point: 65, name: "y_offset", value } => assert!(value.is_nan()),
+            ref problem => panic!("unexpected problem {:?}", problem),
+        }
+        assert_eq!(problems[1], Problem::InvalidMetric { code_point: 66, name: "width", value: -0.5 });
+    }
+
+    #[test]
+    fn shared_cells() {
+        let atlas = test_atlas::atlas(2, 1, 8, 2, &[(32, 0, 0), (0xA0, 0, 0), (65, 0, 1)]);
+
+        assert_eq!(
+            validate(&atlas, &options()),
+            vec![Problem::SharedCell { row: 0, column: 0, code_points: vec![32, 0xA0] }]
+        );
+        let allow_shared = ValidationOptions { allow_shared_cells: true, ..options() };
+        assert_eq!(validate(&atlas, &allow_shared), vec![]);
+    }
+
+    #[test]
+    fn missing_characters() {
+        let code_points: Vec<(usize, usize, usize)> = (0x20..0x7E).map(|code_point| {
+            let index = code_point - 0x20;
+            (code_point, index / 10, index % 10)
+        }).collect();
+        let atlas = test_atlas::atlas(10, 10, 8, 2, &code_points);
+        let required = ValidationOptions { required: vec![CharacterSet::PrintableAscii], ..options() };
+
+        assert_eq!(
+            validate(&atlas, &required),
+            vec![Problem::MissingCharacters { set: CharacterSet::PrintableAscii, code_points: vec![0x7E] }]
+        );
+    }
+
+    #[test]
+    fn image_size_mismatch() {
+        let mut atlas = test_atlas::atlas(2, 1, 8, 2, &[(65, 0, 0)]);
+        atlas.width = 24;
+        atlas.image = vec![0; 4 * 24 * 8];
+
+        assert_eq!(
+            validate(&atlas, &options()),
+            vec![Problem::ImageSize { expected_width: 16, expected_height: 8, width: 24, height: 8 }]
+        );
+    }
+
+    #[test]
+    fn ink_in_the_padding() {
+        let mut atlas = test_atlas::atlas(2, 2, 8, 2, &[(65, 0, 0), (66, 0, 1), (67, 1, 1)]);
+        // Ink inside the glyph area is fine, even right up against the padding.
+        ink(&mut atlas, 0, 0);
+        ink(&mut atlas, 5, 5);
+        // The right band of the first cell, and the bottom band of the last one.
+        ink(&mut atlas, 6, 3);
+        ink(&mut atlas, 7, 3);
+        ink(&mut atlas, 10, 15);
+
+        assert_eq!(validate(&atlas, &options()), vec![
+            Problem::InkInPadding { code_point: 65, pixels: 2 },
+            Problem::InkInPadding { code_point: 67, pixels: 1 },
+        ]);
+    }
+}
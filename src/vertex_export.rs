@@ -0,0 +1,338 @@
+use crate::layout::TextLayout;
+
+use serde_json::json;
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::str;
+
+
+#[derive(Clone, Debug)]
+pub struct ParseVertexFormatError {
+    input: String,
+}
+
+impl fmt::Display for ParseVertexFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid vertex data format `{}`. Expected one of: json, binary, rust, c.", self.input)
+    }
+}
+
+impl error::Error for ParseVertexFormatError {}
+
+/// The file formats the vertex data for laid out text can be written in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VertexFormat {
+    Json,
+    /// A flat little endian blob: the vertex count and index count as `u32`, then the
+    /// positions and texture coordinates as `f32` pairs, then the indices as `u32`.
+    Binary,
+    /// Rust source code declaring the arrays as constants.
+    Rust,
+    /// C source code declaring the arrays as static constants.
+    C,
+}
+
+impl fmt::Display for VertexFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VertexFormat::Json => write!(f, "json"),
+            VertexFormat::Binary => write!(f, "binary"),
+            VertexFormat::Rust => write!(f, "rust"),
+            VertexFormat::C => write!(f, "c"),
+        }
+    }
+}
+
+impl str::FromStr for VertexFormat {
+    type Err = ParseVertexFormatError;
+
+    fn from_str(st: &str) -> Result<VertexFormat, ParseVertexFormatError> {
+        match st {
+            "json" => Ok(VertexFormat::Json),
+            "binary" => Ok(VertexFormat::Binary),
+            "rust" => Ok(VertexFormat::Rust),
+            "c" => Ok(VertexFormat::C),
+            _ => Err(ParseVertexFormatError { input: st.to_string() }),
+        }
+    }
+}
+
+/// Parse the name of the arrays in source code. The arrays are named by appending
+/// `_positions`, `_texcoords` and `_indices` to it, so it must start an identifier in
+/// both Rust and C: ASCII letters, digits and underscores, not starting with a digit.
+pub fn parse_array_name(st: &str) -> Result<String, String> {
+    let mut chars = st.chars();
+    let valid = match chars.next() {
+        Some(first) => {
+            (first.is_ascii_alphabetic() || first == '_') &&
+                chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(format!(
+            "Invalid array name `{}`. Expected ASCII letters, digits and underscores, not starting with a digit.", st
+        ));
+    }
+
+    Ok(st.to_string())
+}
+
+/// Indexed vertex data for a block of laid out text. Each glyph is a quad of four
+/// vertices drawn as two counter clockwise triangles.
+#[derive(Clone, Debug)]
+pub struct VertexData {
+    /// The vertex positions as `(x, y)` pairs in normalized device coordinates.
+    pub positions: Vec<f32>,
+    /// The texture coordinates into the atlas image as `(s, t)` pairs.
+    pub texcoords: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+impl VertexData {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 2
+    }
+}
+
+/// Build indexed vertex data from a text layout. The layout draws each glyph as two
+/// triangles of three vertices each, sharing two corners, so the shared corners are
+/// only kept once.
+pub fn vertex_data(text_layout: &TextLayout) -> VertexData {
+    // The corners of each glyph's six layout vertices that are not repeats: the top left,
+    // bottom left, bottom right and top right corners.
+    let corners = [0, 1, 2, 4];
+    let glyph_count = text_layout.points.len() / 12;
+
+    let mut positions = Vec::with_capacity(8 * glyph_count);
+    let mut texcoords = Vec::with_capacity(8 * glyph_count);
+    let mut indices = Vec::with_capacity(6 * glyph_count);
+    for glyph in 0..glyph_count {
+        for &corner in corners.iter() {
+            let offset = 12 * glyph + 2 * corner;
+            positions.extend_from_slice(&text_layout.points[offset..offset + 2]);
+            texcoords.extend_from_slice(&text_layout.texcoords[offset..offset + 2]);
+        }
+        let base = 4 * glyph as u32;
+        indices.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+    }
+
+    VertexData {
+        positions: positions,
+        texcoords: texcoords,
+        indices: indices,
+    }
+}
+
+fn write_json<W: Write>(data: &VertexData, writer: &mut W) -> io::Result<()> {
+    let value = json!({
+        "vertex_count": data.vertex_count(),
+        "positions": data.positions,
+        "texcoords": data.texcoords,
+        "indices": data.indices,
+    });
+    serde_json::to_writer_pretty(&mut *writer, &value)?;
+    writeln!(writer)
+}
+
+fn write_binary<W: Write>(data: &VertexData, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&(data.vertex_count() as u32).to_le_bytes())?;
+    writer.write_all(&(data.indices.len() as u32).to_le_bytes())?;
+    for value in data.positions.iter().chain(data.texcoords.iter()) {
+        writer.write_all(&value.to_bits().to_le_bytes())?;
+    }
+    for index in data.indices.iter() {
+        writer.write_all(&index.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Write the elements of an array eight to a line.
+fn write_array_elements<W: Write>(writer: &mut W, elements: &[String]) -> io::Result<()> {
+    for line in elements.chunks(8) {
+        writeln!(writer, "    {},", line.join(", "))?;
+    }
+
+    Ok(())
+}
+
+fn write_rust<W: Write>(data: &VertexData, name: &str, writer: &mut W) -> io::Result<()> {
+    let name = name.to_uppercase();
+    let floats = |values: &[f32]| -> Vec<String> { values.iter().map(|value| format!("{:?}", value)).collect() };
+    let indices: Vec<String> = data.indices.iter().map(|index| index.to_string()).collect();
+
+    writeln!(writer, "pub const {}_POSITIONS: [f32; {}] = [", name, data.positions.len())?;
+    write_array_elements(writer, &floats(&data.positions))?;
+    writeln!(writer, "];")?;
+    writeln!(writer)?;
+    writeln!(writer, "pub const {}_TEXCOORDS: [f32; {}] = [", name, data.texcoords.len())?;
+    write_array_elements(writer, &floats(&data.texcoords))?;
+    writeln!(writer, "];")?;
+    writeln!(writer)?;
+    writeln!(writer, "pub const {}_INDICES: [u32; {}] = [", name, data.indices.len())?;
+    write_array_elements(writer, &indices)?;
+    writeln!(writer, "];")
+}
+
+fn write_c<W: Write>(data: &VertexData, name: &str, writer: &mut W) -> io::Result<()> {
+    let name = name.to_lowercase();
+    let floats = |values: &[f32]| -> Vec<String> { values.iter().map(|value| format!("{:?}f", value)).collect() };
+    let indices: Vec<String> = data.indices.iter().map(|index| format!("{}u", index)).collect();
+
+    writeln!(writer, "static const float {}_positions[{}] = {{", name, data.positions.len())?;
+    write_array_elements(writer, &floats(&data.positions))?;
+    writeln!(writer, "}};")?;
+    writeln!(writer)?;
+    writeln!(writer, "static const float {}_texcoords[{}] = {{", name, data.texcoords.len())?;
+    write_array_elements(writer, &floats(&data.texcoords))?;
+    writeln!(writer, "}};")?;
+    writeln!(writer)?;
+    writeln!(writer, "static const unsigned int {}_indices[{}] = {{", name, data.indices.len())?;
+    write_array_elements(writer, &indices)?;
+    writeln!(writer, "}};")
+}
+
+/// Write vertex data in a file format. The arrays in source code are named after
+/// `name`, as checked by `parse_array_name`. C has no empty arrays, so the vertex
+/// data must have at least one glyph.
+pub fn write_vertex_data<W: Write>(
+    data: &VertexData, format: VertexFormat, name: &str, writer: &mut W) -> io::Result<()> {
+
+    match format {
+        VertexFormat::Json => write_json(data, writer),
+        VertexFormat::Binary => write_binary(data, writer),
+        VertexFormat::Rust => write_rust(data, name, writer),
+        VertexFormat::C => write_c(data, name, writer),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout;
+    use crate::layout::TextPlacement;
+    use crate::test_atlas;
+
+    /// A single glyph quad, an eighth of the screen wide and a quarter of it tall.
+    fn quad() -> VertexData {
+        VertexData {
+            positions: vec![0.0, 0.0, 0.0, -0.25, 0.125, -0.25, 0.125, 0.0],
+            texcoords: vec![0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.5, 1.0],
+            indices: vec![0, 1, 2, 2, 3, 0],
+        }
+    }
+
+    fn written(format: VertexFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_vertex_data(&quad(), format, "text", &mut bytes).unwrap();
+
+        bytes
+    }
+
+    #[test]
+    fn shared_corners_are_kept_once() {
+        let atlas = test_atlas::atlas(2, 1, 8, 0, &[(65, 0, 0), (66, 0, 1)]);
+        let placement = TextPlacement::new(0.0, 0.0, std::f32::INFINITY, 16.0);
+        let text_layout = layout::layout_text(&atlas, placement, 128, 64, "AB");
+        let data = vertex_data(&text_layout);
+
+        // The top left, bottom left, bottom right and top right corners of each glyph.
+        assert_eq!(data.positions, vec![
+            0.0, 0.0, 0.0, -0.25, 0.125, -0.25, 0.125, 0.0,
+            0.0625, 0.0, 0.0625, -0.25, 0.1875, -0.25, 0.1875, 0.0,
+        ]);
+        assert_eq!(data.texcoords, vec![
+            0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.5, 1.0,
+            0.5, 1.0, 0.5, 0.0, 1.0, 0.0, 1.0, 1.0,
+        ]);
+        assert_eq!(data.indices, vec![0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4]);
+        assert_eq!(data.vertex_count(), 8);
+        // The indexed triangles are the ones the layout draws, in the same order.
+        for (vertex, &index) in data.indices.iter().enumerate() {
+            let index = index as usize;
+            assert_eq!(&data.positions[2 * index..2 * index + 2], &text_layout.points[2 * vertex..2 * vertex + 2]);
+            assert_eq!(&data.texcoords[2 * index..2 * index + 2], &text_layout.texcoords[2 * vertex..2 * vertex + 2]);
+        }
+    }
+
+    #[test]
+    fn binary_layout() {
+        let bytes = written(VertexFormat::Binary);
+        let word = |index: usize| -> u32 {
+            let mut word = [0; 4];
+            word.copy_from_slice(&bytes[4 * index..4 * index + 4]);
+            u32::from_le_bytes(word)
+        };
+
+        assert_eq!(bytes.len(), 4 * (2 + 8 + 8 + 6));
+        assert_eq!((word(0), word(1)), (4, 6));
+        let floats: Vec<f32> = (2..18).map(|index| f32::from_bits(word(index))).collect();
+        assert_eq!(&floats[..8], &quad().positions[..]);
+        assert_eq!(&floats[8..], &quad().texcoords[..]);
+        let indices: Vec<u32> = (18..24).map(word).collect();
+        assert_eq!(indices, quad().indices);
+    }
+
+    #[test]
+    fn json_arrays() {
+        let value: serde_json::Value = serde_json::from_slice(&written(VertexFormat::Json)).unwrap();
+
+        assert_eq!(value, json!({
+            "vertex_count": 4,
+            "positions": [0.0, 0.0, 0.0, -0.25, 0.125, -0.25, 0.125, 0.0],
+            "texcoords": [0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.5, 1.0],
+            "indices": [0, 1, 2, 2, 3, 0],
+        }));
+    }
+
+    #[test]
+    fn rust_arrays() {
+        let expected = "\
+pub const TEXT_POSITIONS: [f32; 8] = [
+    0.0, 0.0, 0.0, -0.25, 0.125, -0.25, 0.125, 0.0,
+];
+
+pub const TEXT_TEXCOORDS: [f32; 8] = [
+    0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.5, 1.0,
+];
+
+pub const TEXT_INDICES: [u32; 6] = [
+    0, 1, 2, 2, 3, 0,
+];
+";
+
+        assert_eq!(String::from_utf8(written(VertexFormat::Rust)).unwrap(), expected);
+    }
+
+    #[test]
+    fn c_arrays() {
+        let expected = "\
+static const float text_positions[8] = {
+    0.0f, 0.0f, 0.0f, -0.25f, 0.125f, -0.25f, 0.125f, 0.0f,
+};
+
+static const float text_texcoords[8] = {
+    0.0f, 1.0f, 0.0f, 0.0f, 0.5f, 0.0f, 0.5f, 1.0f,
+};
+
+static const unsigned int text_indices[6] = {
+    0u, 1u, 2u, 2u, 3u, 0u,
+};
+";
+
+        assert_eq!(String::from_utf8(written(VertexFormat::C)).unwrap(), expected);
+    }
+
+    #[test]
+    fn array_names_are_identifiers() {
+        assert_eq!(parse_array_name("_title_1"), Ok(String::from("_title_1")));
+        for &name in ["", "1st", "main-menu", "caf\u{e9}"].iter() {
+            assert!(parse_array_name(name).is_err(), "{} should not be a valid name", name);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+
+/// How often a watched file's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+
+/// Watch a file for changes by polling its modification time. Polling keeps the
+/// viewer free of platform specific file notification APIs, and checking a couple
+/// of times a second is plenty for files saved by hand.
+#[derive(Clone, Debug)]
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl FileWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> FileWatcher {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+
+        FileWatcher {
+            path: path,
+            modified: modified,
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Determine whether the file changed since it was last checked. A file that is
+    /// missing, for instance while an editor replaces it, does not count as changed
+    /// until it comes back.
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        match modified_time(&self.path) {
+            Some(modified) if Some(modified) != self.modified => {
+                self.modified = Some(modified);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}